@@ -0,0 +1,117 @@
+//! Support for associated data split across multiple buffers.
+
+use crate::{ChaChaBox, Nonce, Tag};
+use chacha20::{
+    cipher::{NewCipher, StreamCipher, StreamCipherSeek},
+    XChaCha20,
+};
+use poly1305::{
+    universal_hash::{NewUniversalHash, UniversalHash},
+    Poly1305,
+};
+use xsalsa20poly1305::aead::{generic_array::GenericArray, Error};
+use zeroize::Zeroize;
+
+/// Size of a ChaCha20 block in bytes.
+const BLOCK_SIZE: u64 = 64;
+
+impl ChaChaBox {
+    /// Encrypt the given buffer in-place, authenticating the concatenation of
+    /// the given associated data chunks without requiring them to be
+    /// contiguous in memory.
+    ///
+    /// The returned tag is identical to the one produced by
+    /// [`AeadInPlace::encrypt_in_place_detached`][`crate::aead::AeadInPlace::encrypt_in_place_detached`]
+    /// with the concatenated associated data.
+    pub fn encrypt_in_place_detached_with_aad_chunks(
+        &self,
+        nonce: &Nonce,
+        associated_data: &[&[u8]],
+        buffer: &mut [u8],
+    ) -> Result<Tag, Error> {
+        let (mut cipher, mut mac) = self.init_chunked(nonce);
+        let associated_data_len = update_padded_chunks(&mut mac, associated_data);
+
+        cipher.try_apply_keystream(buffer).map_err(|_| Error)?;
+        mac.update_padded(buffer);
+        authenticate_lengths(&mut mac, associated_data_len, buffer.len());
+
+        Ok(mac.finalize().into_bytes())
+    }
+
+    /// Decrypt the given buffer in-place, authenticating the concatenation of
+    /// the given associated data chunks without requiring them to be
+    /// contiguous in memory.
+    pub fn decrypt_in_place_detached_with_aad_chunks(
+        &self,
+        nonce: &Nonce,
+        associated_data: &[&[u8]],
+        buffer: &mut [u8],
+        tag: &Tag,
+    ) -> Result<(), Error> {
+        let (mut cipher, mut mac) = self.init_chunked(nonce);
+        let associated_data_len = update_padded_chunks(&mut mac, associated_data);
+
+        mac.update_padded(buffer);
+        authenticate_lengths(&mut mac, associated_data_len, buffer.len());
+
+        // This performs a constant-time comparison using the `subtle` crate
+        mac.verify(tag).map_err(|_| Error)?;
+        cipher.try_apply_keystream(buffer).map_err(|_| Error)
+    }
+
+    fn init_chunked(&self, nonce: &Nonce) -> (XChaCha20, Poly1305) {
+        let mut cipher = XChaCha20::new(GenericArray::from_slice(&*self.key), nonce);
+
+        // Derive Poly1305 key from the first 32-bytes of the ChaCha20 keystream
+        let mut mac_key = poly1305::Key::default();
+        cipher.apply_keystream(&mut mac_key);
+        let mac = Poly1305::new(&mac_key);
+        mac_key.zeroize();
+
+        // Set ChaCha20 counter to 1
+        cipher.seek(BLOCK_SIZE);
+
+        (cipher, mac)
+    }
+}
+
+/// Feed the concatenation of the given chunks to Poly1305, padding the final
+/// block with zeroes. Returns the total length of the chunks.
+fn update_padded_chunks(mac: &mut Poly1305, chunks: &[&[u8]]) -> u64 {
+    let mut block = poly1305::Block::default();
+    let mut filled = 0;
+    let mut total_len = 0u64;
+
+    for chunk in chunks {
+        total_len += chunk.len() as u64;
+
+        let mut chunk = *chunk;
+        while !chunk.is_empty() {
+            let n = core::cmp::min(block.len() - filled, chunk.len());
+            block[filled..filled + n].copy_from_slice(&chunk[..n]);
+            filled += n;
+            chunk = &chunk[n..];
+
+            if filled == block.len() {
+                mac.update(&block);
+                filled = 0;
+            }
+        }
+    }
+
+    if filled > 0 {
+        block[filled..].iter_mut().for_each(|byte| *byte = 0);
+        mac.update(&block);
+    }
+
+    total_len
+}
+
+/// Authenticate the lengths of the associated data and message.
+fn authenticate_lengths(mac: &mut Poly1305, associated_data_len: u64, buffer_len: usize) {
+    let mut block = poly1305::Block::default();
+    block[..8].copy_from_slice(&associated_data_len.to_le_bytes());
+    block[8..].copy_from_slice(&(buffer_len as u64).to_le_bytes());
+    mac.update(&block);
+}
@@ -0,0 +1,37 @@
+//! Runtime information about the X25519 backend selected for this build.
+
+/// Information about the `curve25519-dalek` arithmetic backend used for
+/// X25519, as selected by the `u32_backend` or `u64_backend` feature.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct BackendInfo {
+    /// Name of the backend, i.e. `"u32"` or `"u64"`.
+    pub name: &'static str,
+
+    /// Whether the backend is expected to run in constant time on the
+    /// target platform.
+    ///
+    /// This is a best-effort flag derived from compile-time configuration,
+    /// not a guarantee about the generated machine code. Both backends are
+    /// written to be constant time, but the `u64` backend relies on 128-bit
+    /// multiplications, which are emulated in software on targets without
+    /// 64-bit pointers and aren't guaranteed to be constant time there.
+    pub constant_time: bool,
+}
+
+/// Get information about the X25519 backend selected for this build.
+pub fn backend_info() -> BackendInfo {
+    #[cfg(feature = "u64_backend")]
+    let info = BackendInfo {
+        name: "u64",
+        constant_time: cfg!(target_pointer_width = "64"),
+    };
+
+    #[cfg(all(feature = "u32_backend", not(feature = "u64_backend")))]
+    let info = BackendInfo {
+        name: "u32",
+        constant_time: true,
+    };
+
+    info
+}
@@ -0,0 +1,99 @@
+//! Base64 encoding of keys, as used by human-readable serde formats.
+//!
+//! This uses the standard alphabet with padding (RFC 4648 section 4). The
+//! encoding and decoding avoid secret-dependent branches and table lookups,
+//! since it is also used for secret key material.
+
+use crate::KEY_SIZE;
+
+/// Length of a Base64-encoded key.
+pub(crate) const ENCODED_LEN: usize = ((KEY_SIZE - 1) / 3 + 1) * 4;
+
+/// Encode a key as Base64.
+pub(crate) fn encode(bytes: &[u8; KEY_SIZE]) -> [u8; ENCODED_LEN] {
+    let mut encoded = [b'='; ENCODED_LEN];
+
+    for (chunk, out) in bytes.chunks(3).zip(encoded.chunks_mut(4)) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).copied().unwrap_or(0);
+        let b2 = chunk.get(2).copied().unwrap_or(0);
+        let sextets = [
+            b0 >> 2,
+            (b0 << 4 | b1 >> 4) & 0x3f,
+            (b1 << 2 | b2 >> 6) & 0x3f,
+            b2 & 0x3f,
+        ];
+
+        // A chunk of n bytes is encoded as n + 1 characters, plus padding
+        for (c, &sextet) in out.iter_mut().zip(sextets.iter()).take(chunk.len() + 1) {
+            *c = encode_sextet(sextet);
+        }
+    }
+
+    encoded
+}
+
+/// Decode a Base64-encoded key.
+///
+/// Returns `None` if the input is not the canonical encoding of a key.
+pub(crate) fn decode(encoded: &[u8]) -> Option<[u8; KEY_SIZE]> {
+    if encoded.len() != ENCODED_LEN || encoded[ENCODED_LEN - 1] != b'=' {
+        return None;
+    }
+
+    let mut bytes = [0u8; KEY_SIZE];
+    let mut invalid = 0i16;
+
+    for (chunk, out) in encoded.chunks(4).zip(bytes.chunks_mut(3)) {
+        let mut sextets = [0i16; 4];
+        for (sextet, &c) in sextets.iter_mut().zip(chunk.iter()).take(out.len() + 1) {
+            *sextet = decode_sextet(c);
+            invalid |= *sextet;
+        }
+
+        let decoded = [
+            (sextets[0] << 2 | sextets[1] >> 4) as u8,
+            (sextets[1] << 4 | sextets[2] >> 2) as u8,
+            (sextets[2] << 6 | sextets[3]) as u8,
+        ];
+        out.copy_from_slice(&decoded[..out.len()]);
+    }
+
+    // The last character only carries 4 bits: reject non-canonical encodings
+    // which have the 2 unused bits set
+    invalid |= -(decode_sextet(encoded[ENCODED_LEN - 2]) & 3);
+
+    if invalid < 0 {
+        None
+    } else {
+        Some(bytes)
+    }
+}
+
+/// Encode 6 bits as a Base64 character.
+fn encode_sextet(sextet: u8) -> u8 {
+    let sextet = i16::from(sextet);
+    let mut diff = i16::from(b'A');
+
+    diff += ((25 - sextet) >> 8) & 6;
+    diff -= ((51 - sextet) >> 8) & 75;
+    diff -= ((61 - sextet) >> 8) & 15;
+    diff += ((62 - sextet) >> 8) & 3;
+
+    (sextet + diff) as u8
+}
+
+/// Decode a Base64 character into 6 bits, or a negative value if it is not
+/// part of the alphabet.
+fn decode_sextet(c: u8) -> i16 {
+    let c = i16::from(c);
+    let mut sextet = -1;
+
+    sextet += (((0x40 - c) & (c - 0x5b)) >> 8) & (c - 64);
+    sextet += (((0x60 - c) & (c - 0x7b)) >> 8) & (c - 70);
+    sextet += (((0x2f - c) & (c - 0x3a)) >> 8) & (c + 5);
+    sextet += (((0x2a - c) & (c - 0x2c)) >> 8) & 63;
+    sextet += (((0x2e - c) & (c - 0x30)) >> 8) & 64;
+
+    sextet
+}
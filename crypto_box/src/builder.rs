@@ -0,0 +1,154 @@
+//! Builder for boxes which manage their own nonces.
+
+use crate::{nonce::NonceCounter, Nonce};
+use rand_core::CryptoRngCore;
+use xsalsa20poly1305::aead::{consts::U24, AeadInPlace, Buffer, Error};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Strategy used by a [`NoncedBox`] to select the nonce for each message.
+pub enum NonceStrategy<'a> {
+    /// Generate a fresh random nonce for every message.
+    Random(&'a mut dyn CryptoRngCore),
+
+    /// Use a counter starting at the given nonce, incremented after every
+    /// message. See [`NonceCounter`].
+    Counter(Nonce),
+
+    /// Use nonces supplied by the caller via [`NoncedBox::set_nonce`].
+    ///
+    /// Each supplied nonce is used for exactly one message.
+    External,
+}
+
+/// Builder for a [`NoncedBox`].
+pub struct BoxBuilder<'a, B> {
+    aead: B,
+    strategy: NonceStrategy<'a>,
+}
+
+impl<'a, B> BoxBuilder<'a, B>
+where
+    B: AeadInPlace<NonceSize = U24>,
+{
+    /// Create a new [`BoxBuilder`] for the given box.
+    ///
+    /// The nonce strategy defaults to [`NonceStrategy::External`].
+    pub fn new(aead: B) -> Self {
+        Self {
+            aead,
+            strategy: NonceStrategy::External,
+        }
+    }
+
+    /// Set the [`NonceStrategy`] used by the box.
+    pub fn nonce_strategy(mut self, strategy: NonceStrategy<'a>) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Build the [`NoncedBox`].
+    pub fn build(self) -> NoncedBox<'a, B> {
+        let source = match self.strategy {
+            NonceStrategy::Random(csprng) => NonceSource::Random(csprng),
+            NonceStrategy::Counter(initial) => NonceSource::Counter(NonceCounter::new(initial)),
+            NonceStrategy::External => NonceSource::External(None),
+        };
+
+        NoncedBox {
+            aead: self.aead,
+            source,
+        }
+    }
+}
+
+/// Box which applies a [`NonceStrategy`] to every message it encrypts.
+///
+/// Constructed using a [`BoxBuilder`].
+pub struct NoncedBox<'a, B> {
+    aead: B,
+    source: NonceSource<'a>,
+}
+
+impl<'a, B> NoncedBox<'a, B>
+where
+    B: AeadInPlace<NonceSize = U24>,
+{
+    /// Supply the nonce for the next message when using
+    /// [`NonceStrategy::External`].
+    ///
+    /// Returns an error if the box uses a different strategy.
+    pub fn set_nonce(&mut self, nonce: Nonce) -> Result<(), Error> {
+        match &mut self.source {
+            NonceSource::External(next) => {
+                *next = Some(nonce);
+                Ok(())
+            }
+            _ => Err(Error),
+        }
+    }
+
+    /// Encrypt the given buffer in-place, returning the nonce which was used.
+    pub fn encrypt_in_place(
+        &mut self,
+        associated_data: &[u8],
+        buffer: &mut dyn Buffer,
+    ) -> Result<Nonce, Error> {
+        let nonce = self.source.next_nonce()?;
+        self.aead
+            .encrypt_in_place(&nonce, associated_data, buffer)?;
+        Ok(nonce)
+    }
+
+    /// Encrypt the given plaintext, returning the nonce which was used along
+    /// with the ciphertext.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt(
+        &mut self,
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Result<(Nonce, Vec<u8>), Error> {
+        let mut buffer = Vec::from(plaintext);
+        let nonce = self.encrypt_in_place(associated_data, &mut buffer)?;
+        Ok((nonce, buffer))
+    }
+
+    /// Encrypt the given plaintext without associated data under the next
+    /// nonce, returning that nonce along with the ciphertext.
+    ///
+    /// With [`NonceStrategy::Counter`], every call advances the counter, so
+    /// a nonce can't be reused by construction.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt_next(&mut self, plaintext: &[u8]) -> Result<(Nonce, Vec<u8>), Error> {
+        self.encrypt(b"", plaintext)
+    }
+
+    /// Get the inner box, e.g. for decrypting messages.
+    pub fn inner(&self) -> &B {
+        &self.aead
+    }
+}
+
+/// Internal state of a [`NonceStrategy`].
+enum NonceSource<'a> {
+    Random(&'a mut dyn CryptoRngCore),
+    Counter(NonceCounter),
+    External(Option<Nonce>),
+}
+
+impl<'a> NonceSource<'a> {
+    fn next_nonce(&mut self) -> Result<Nonce, Error> {
+        match self {
+            NonceSource::Random(csprng) => {
+                let mut nonce = Nonce::default();
+                csprng.fill_bytes(&mut nonce);
+                Ok(nonce)
+            }
+            NonceSource::Counter(counter) => counter.next_nonce(),
+            NonceSource::External(next) => next.take().ok_or(Error),
+        }
+    }
+}
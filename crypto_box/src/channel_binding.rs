@@ -0,0 +1,82 @@
+//! Boxes which bind both parties' public keys into every message.
+
+use crate::{ChaChaBox, Nonce, PublicKey, Tag, KEY_SIZE};
+use xsalsa20poly1305::aead::{
+    consts::{U0, U16, U24},
+    AeadCore, AeadInPlace, Error,
+};
+
+impl ChaChaBox {
+    /// Bind both parties' public keys into every message encrypted or
+    /// decrypted with this box, returning a [`ChannelBoundBox`].
+    ///
+    /// The keys are ordered canonically, so that both parties derive the same
+    /// binding from their own point of view.
+    pub fn with_channel_binding(self, our_pk: &PublicKey, their_pk: &PublicKey) -> ChannelBoundBox {
+        let (first, second) = if our_pk <= their_pk {
+            (our_pk, their_pk)
+        } else {
+            (their_pk, our_pk)
+        };
+
+        let mut binding = [0u8; 2 * KEY_SIZE];
+        binding[..KEY_SIZE].copy_from_slice(first.as_bytes());
+        binding[KEY_SIZE..].copy_from_slice(second.as_bytes());
+
+        ChannelBoundBox {
+            inner: self,
+            binding,
+        }
+    }
+}
+
+/// [`ChaChaBox`] which authenticates both parties' public keys as part of
+/// the associated data of every message.
+///
+/// This prevents identity misbinding (unknown key share) attacks: a message
+/// is only accepted by a box bound to the same pair of public keys.
+/// Associated data passed to the [`AeadInPlace`] methods is authenticated
+/// after the keys.
+///
+/// Constructed using [`ChaChaBox::with_channel_binding`].
+#[derive(Clone)]
+pub struct ChannelBoundBox {
+    inner: ChaChaBox,
+    binding: [u8; 2 * KEY_SIZE],
+}
+
+impl AeadCore for ChannelBoundBox {
+    type NonceSize = U24;
+    type TagSize = U16;
+    type CiphertextOverhead = U0;
+}
+
+impl AeadInPlace for ChannelBoundBox {
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Tag, Error> {
+        self.inner.encrypt_in_place_detached_with_aad_chunks(
+            nonce,
+            &[&self.binding, associated_data],
+            buffer,
+        )
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag,
+    ) -> Result<(), Error> {
+        self.inner.decrypt_in_place_detached_with_aad_chunks(
+            nonce,
+            &[&self.binding, associated_data],
+            buffer,
+            tag,
+        )
+    }
+}
@@ -0,0 +1,49 @@
+//! Free functions named after their equivalents in `sodiumoxide`'s
+//! `crypto::box_` module (also used by `dryoc`), to ease migrating existing
+//! code.
+//!
+//! These are thin wrappers over [`SalsaBox`]. The argument order follows
+//! `sodiumoxide` (message first), but errors are reported as
+//! [`aead::Error`] rather than `()`.
+
+use crate::{aead, generate_nonce, Nonce, PrecomputedKey, PublicKey, SalsaBox, SecretKey};
+use rand_core::OsRng;
+use std::vec::Vec;
+use xsalsa20poly1305::aead::Aead;
+
+/// Generate a random keypair using the operating system's RNG.
+pub fn gen_keypair() -> (PublicKey, SecretKey) {
+    let secret_key = SecretKey::generate(&mut OsRng);
+    (secret_key.public_key(), secret_key)
+}
+
+/// Generate a random nonce using the operating system's RNG.
+pub fn gen_nonce() -> Nonce {
+    generate_nonce(&mut OsRng)
+}
+
+/// Precompute the shared key for the given keys, for use with
+/// [`seal_precomputed`] and [`open_precomputed`].
+pub fn precompute(pk: &PublicKey, sk: &SecretKey) -> PrecomputedKey {
+    PrecomputedKey::new(pk, sk)
+}
+
+/// Encrypt and authenticate a message from `sk` to `pk`.
+pub fn seal(m: &[u8], n: &Nonce, pk: &PublicKey, sk: &SecretKey) -> Result<Vec<u8>, aead::Error> {
+    SalsaBox::new(pk, sk).encrypt(n, m)
+}
+
+/// Verify and decrypt a message from `pk` to `sk`.
+pub fn open(c: &[u8], n: &Nonce, pk: &PublicKey, sk: &SecretKey) -> Result<Vec<u8>, aead::Error> {
+    SalsaBox::new(pk, sk).decrypt(n, c)
+}
+
+/// Encrypt and authenticate a message using a precomputed key.
+pub fn seal_precomputed(m: &[u8], n: &Nonce, k: &PrecomputedKey) -> Result<Vec<u8>, aead::Error> {
+    SalsaBox::from(k).encrypt(n, m)
+}
+
+/// Verify and decrypt a message using a precomputed key.
+pub fn open_precomputed(c: &[u8], n: &Nonce, k: &PrecomputedKey) -> Result<Vec<u8>, aead::Error> {
+    SalsaBox::from(k).decrypt(n, c)
+}
@@ -0,0 +1,40 @@
+//! Directional keys for bidirectional channels.
+
+use crate::{SalsaBox, DUPLEX_INFO};
+
+/// Separate boxes for sending and receiving messages over a bidirectional
+/// channel.
+///
+/// Both directions are derived from the same [`SalsaBox`] with HKDF-SHA256
+/// using distinct labels, so that a message sent by one party can't be
+/// reflected back to it: its `recv` box rejects messages encrypted with its
+/// own `send` box.
+pub struct DuplexKeys {
+    /// Box used to encrypt messages sent to the peer.
+    pub send: SalsaBox,
+
+    /// Box used to decrypt messages received from the peer.
+    pub recv: SalsaBox,
+}
+
+impl DuplexKeys {
+    /// Derive the [`DuplexKeys`] for one end of a channel.
+    ///
+    /// Both parties must derive them from the same [`SalsaBox`] (i.e. from
+    /// each other's public keys) and agree on their roles: exactly one of
+    /// them must be the initiator. The initiator's `send` box then matches the
+    /// responder's `recv` box, and vice versa.
+    pub fn derive(salsa_box: &SalsaBox, is_initiator: bool) -> Self {
+        let [initiator, responder] = DUPLEX_INFO;
+        let (send, recv) = if is_initiator {
+            (initiator, responder)
+        } else {
+            (responder, initiator)
+        };
+
+        DuplexKeys {
+            send: salsa_box.derive_subkey(&[send]),
+            recv: salsa_box.derive_subkey(&[recv]),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! Conversion of Ed25519 keys into X25519 keys, compatible with libsodium's
+//! `crypto_sign_ed25519_pk_to_curve25519` and
+//! `crypto_sign_ed25519_sk_to_curve25519`.
+//!
+//! This allows encrypting to parties which only publish an Ed25519 identity
+//! key. Note that using the same key pair for both signing and encryption is
+//! generally discouraged, unless the protocol has been designed for it.
+
+use crate::{clamp, Error, PublicKey, SecretKey, KEY_SIZE};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use sha2::{Digest, Sha512};
+use zeroize::{Zeroize, Zeroizing};
+
+#[cfg_attr(docsrs, doc(cfg(feature = "ed25519")))]
+impl PublicKey {
+    /// Convert an Ed25519 public key into the corresponding X25519
+    /// [`PublicKey`], by mapping the Edwards point to its Montgomery form.
+    ///
+    /// Returns [`Error::InvalidEd25519Key`] if the bytes are not a valid
+    /// Edwards point encoding, if the point has small order, or if it is not
+    /// in the prime-order subgroup (as libsodium also checks).
+    pub fn from_ed25519(ed25519_pk: &[u8; KEY_SIZE]) -> Result<Self, Error> {
+        let point = CompressedEdwardsY(*ed25519_pk)
+            .decompress()
+            .filter(|point| !point.is_small_order() && point.is_torsion_free())
+            .ok_or(Error::InvalidEd25519Key)?;
+
+        Ok(PublicKey(point.to_montgomery().to_bytes()))
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "ed25519")))]
+impl SecretKey {
+    /// Convert a 32-byte Ed25519 secret key seed into the corresponding X25519
+    /// [`SecretKey`].
+    ///
+    /// The key is the clamped first half of the SHA-512 hash of the seed,
+    /// i.e. the same scalar Ed25519 uses for signing.
+    pub fn from_ed25519_seed(seed: &[u8; KEY_SIZE]) -> Self {
+        let mut hash = Sha512::digest(seed);
+        let mut scalar = Zeroizing::new([0u8; KEY_SIZE]);
+        scalar.copy_from_slice(&hash[..KEY_SIZE]);
+        hash.as_mut_slice().zeroize();
+        clamp(&mut scalar);
+        SecretKey(*scalar)
+    }
+}
@@ -0,0 +1,134 @@
+//! Errors generated by this crate.
+
+use core::fmt::{self, Display};
+use xsalsa20poly1305::aead;
+
+/// Error type.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Given object is of an unexpected length.
+    InvalidLength {
+        /// Expected length.
+        expected: usize,
+
+        /// Actual length.
+        got: usize,
+    },
+
+    /// Hex-encoded input has an odd number of characters.
+    OddLength,
+
+    /// Hex-encoded input contains a character which isn't a hex digit.
+    InvalidHexCharacter,
+
+    /// Z85-encoded input contains a character outside of the Z85 alphabet,
+    /// or a group which overflows 32 bits.
+    InvalidZ85,
+
+    /// Ed25519 public key is not a valid Edwards point, or has small order.
+    InvalidEd25519Key,
+
+    /// Public key doesn't match the one derived from the secret key.
+    PublicKeyMismatch,
+
+    /// Key derivation path is malformed, or contains a non-hardened index.
+    InvalidDerivationPath,
+
+    /// Nonce is all zeros, which usually indicates it was never initialized.
+    ZeroNonce,
+
+    /// Nonce is of an unexpected length.
+    InvalidNonceLength {
+        /// Expected length.
+        expected: usize,
+
+        /// Actual length.
+        got: usize,
+    },
+
+    /// Encryption failed, or the ciphertext failed to authenticate.
+    Aead,
+
+    /// Message authenticated, but its timestamp is older than allowed.
+    Expired,
+
+    /// Ciphertext is too short to contain an authentication tag.
+    CiphertextTooShort {
+        /// Minimum ciphertext length.
+        min: usize,
+
+        /// Actual ciphertext length.
+        got: usize,
+    },
+
+    /// Plaintext is larger than the allowed maximum.
+    TooLarge {
+        /// Maximum plaintext length.
+        max: usize,
+
+        /// Actual plaintext length.
+        got: usize,
+    },
+
+    /// Output buffer is too small to hold the result.
+    BufferTooSmall {
+        /// Required capacity.
+        required: usize,
+
+        /// Actual capacity.
+        capacity: usize,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidLength { expected, got } => f.write_fmt(format_args!(
+                "invalid length: expected {} but got {}",
+                expected, got,
+            )),
+            Error::OddLength => f.write_str("hex input has an odd number of characters"),
+            Error::InvalidHexCharacter => f.write_str("hex input contains an invalid character"),
+            Error::InvalidZ85 => f.write_str("invalid Z85 input"),
+            Error::InvalidEd25519Key => f.write_str("invalid Ed25519 public key"),
+            Error::PublicKeyMismatch => f.write_str("public key does not match secret key"),
+            Error::InvalidDerivationPath => f.write_str("invalid key derivation path"),
+            Error::ZeroNonce => f.write_str("nonce is all zeros"),
+            Error::InvalidNonceLength { expected, got } => f.write_fmt(format_args!(
+                "invalid nonce length: expected {} but got {}",
+                expected, got,
+            )),
+            Error::Aead => f.write_str("AEAD operation failed"),
+            Error::Expired => f.write_str("message has expired"),
+            Error::CiphertextTooShort { min, got } => f.write_fmt(format_args!(
+                "ciphertext too short: expected at least {} bytes but got {}",
+                min, got,
+            )),
+            Error::TooLarge { max, got } => f.write_fmt(format_args!(
+                "plaintext too large: expected at most {} bytes but got {}",
+                max, got,
+            )),
+            Error::BufferTooSmall { required, capacity } => f.write_fmt(format_args!(
+                "buffer too small: {} bytes required but capacity is {}",
+                required, capacity,
+            )),
+        }
+    }
+}
+
+impl From<aead::Error> for Error {
+    fn from(_: aead::Error) -> Error {
+        Error::Aead
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "core_error")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for Error {}
+
+/// Requires Rust 1.81 or later, where [`std::error::Error`] is a re-export of
+/// this trait: this impl also covers it when the `std` feature is enabled.
+#[cfg(feature = "core_error")]
+#[cfg_attr(docsrs, doc(cfg(feature = "core_error")))]
+impl core::error::Error for Error {}
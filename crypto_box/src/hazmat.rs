@@ -0,0 +1,50 @@
+//! Low-level key derivation functions used by the box constructors.
+//!
+//! # ☢️️ WARNING: HAZARDOUS API ☢️
+//!
+//! These functions operate on raw X25519 shared secrets and derived keys.
+//! They are exposed for interoperability and verification purposes, and are
+//! not needed for typical usage of this crate. Misuse can easily result in
+//! insecure protocols.
+
+#[cfg(feature = "hazmat-reduced")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hazmat-reduced")))]
+pub mod reduced;
+
+use crate::KEY_SIZE;
+use chacha20::hchacha;
+use salsa20::hsalsa20;
+use xsalsa20poly1305::aead::generic_array::GenericArray;
+use zeroize::Zeroize;
+
+/// Derive a [`SalsaBox`][`crate::SalsaBox`] key from a raw X25519 shared
+/// secret by applying HSalsa20 with an all-zero nonce.
+///
+/// This is the derivation performed by libsodium's `crypto_box_beforenm`.
+pub fn hsalsa_derive(shared_secret: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    let mut key = hsalsa20(
+        GenericArray::from_slice(shared_secret),
+        &GenericArray::default(),
+    );
+
+    let mut derived_key = [0u8; KEY_SIZE];
+    derived_key.copy_from_slice(&key);
+    key.zeroize();
+
+    derived_key
+}
+
+/// Derive a [`ChaChaBox`][`crate::ChaChaBox`] key from a raw X25519 shared
+/// secret by applying HChaCha20 with an all-zero nonce.
+pub fn hchacha_derive(shared_secret: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    let mut key = hchacha::<chacha20::R20>(
+        GenericArray::from_slice(shared_secret),
+        &GenericArray::default(),
+    );
+
+    let mut derived_key = [0u8; KEY_SIZE];
+    derived_key.copy_from_slice(&key);
+    key.zeroize();
+
+    derived_key
+}
@@ -0,0 +1,150 @@
+//! Reduced-round key derivation, for cryptanalysis research.
+//!
+//! # ☢️️ WARNING: INSECURE ☢️
+//!
+//! Boxes constructed using this module use a reduced number of HSalsa20 or
+//! HChaCha20 rounds to derive their key from the X25519 shared secret, and
+//! are NOT SECURE. They do not interoperate with any other implementation.
+//! This module exists solely to study the key derivation step, and must never
+//! be used in production.
+//!
+//! Only the key derivation is affected: the XSalsa20Poly1305 and
+//! XChaCha20Poly1305 ciphers themselves always use the full 20 rounds.
+//!
+//! Using this module takes two deliberate steps: enabling the
+//! `hazmat-reduced` feature, and building with
+//! `RUSTFLAGS="--cfg crypto_box_hazmat_reduced"`. The feature alone is a
+//! compile error, so it can't be enabled by a dependency by accident.
+
+use crate::{ChaChaBox, DiffieHellman, PrecomputedKey, PublicKey, SalsaBox, KEY_SIZE};
+use xsalsa20poly1305::aead::generic_array::GenericArray;
+use zeroize::{Zeroize, Zeroizing};
+
+/// "expand 32-byte k"
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+/// Derive a [`SalsaBox`] key from a raw X25519 shared secret using HSalsa
+/// with the given number of rounds and an all-zero nonce.
+///
+/// Rounds alternate between column and diagonal rounds, starting with a
+/// column round, so 20 rounds is equivalent to
+/// [`hazmat::hsalsa_derive`][`super::hsalsa_derive`].
+pub fn hsalsa_derive(rounds: usize, shared_secret: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    let key = load_words(shared_secret);
+    let mut state = [0u32; 16];
+    state[0] = CONSTANTS[0];
+    state[5] = CONSTANTS[1];
+    state[10] = CONSTANTS[2];
+    state[15] = CONSTANTS[3];
+    state[1..5].copy_from_slice(&key[..4]);
+    state[11..15].copy_from_slice(&key[4..]);
+
+    for round in 0..rounds {
+        if round % 2 == 0 {
+            salsa_quarter_round(&mut state, 0, 4, 8, 12);
+            salsa_quarter_round(&mut state, 5, 9, 13, 1);
+            salsa_quarter_round(&mut state, 10, 14, 2, 6);
+            salsa_quarter_round(&mut state, 15, 3, 7, 11);
+        } else {
+            salsa_quarter_round(&mut state, 0, 1, 2, 3);
+            salsa_quarter_round(&mut state, 5, 6, 7, 4);
+            salsa_quarter_round(&mut state, 10, 11, 8, 9);
+            salsa_quarter_round(&mut state, 15, 12, 13, 14);
+        }
+    }
+
+    let output = [
+        state[0], state[5], state[10], state[15], state[6], state[7], state[8], state[9],
+    ];
+    state.zeroize();
+    store_words(&output)
+}
+
+/// Derive a [`ChaChaBox`] key from a raw X25519 shared secret using HChaCha
+/// with the given number of rounds and an all-zero nonce.
+///
+/// Rounds alternate between column and diagonal rounds, starting with a
+/// column round, so 20 rounds is equivalent to
+/// [`hazmat::hchacha_derive`][`super::hchacha_derive`].
+pub fn hchacha_derive(rounds: usize, shared_secret: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&CONSTANTS);
+    state[4..12].copy_from_slice(&*load_words(shared_secret));
+
+    for round in 0..rounds {
+        if round % 2 == 0 {
+            chacha_quarter_round(&mut state, 0, 4, 8, 12);
+            chacha_quarter_round(&mut state, 1, 5, 9, 13);
+            chacha_quarter_round(&mut state, 2, 6, 10, 14);
+            chacha_quarter_round(&mut state, 3, 7, 11, 15);
+        } else {
+            chacha_quarter_round(&mut state, 0, 5, 10, 15);
+            chacha_quarter_round(&mut state, 1, 6, 11, 12);
+            chacha_quarter_round(&mut state, 2, 7, 8, 13);
+            chacha_quarter_round(&mut state, 3, 4, 9, 14);
+        }
+    }
+
+    let output = [
+        state[0], state[1], state[2], state[3], state[12], state[13], state[14], state[15],
+    ];
+    state.zeroize();
+    store_words(&output)
+}
+
+/// Create a [`SalsaBox`] whose key is derived using
+/// [`hsalsa_derive`] with the given number of rounds.
+pub fn salsa_box<S>(rounds: usize, public_key: &PublicKey, secret_key: &S) -> SalsaBox
+where
+    S: DiffieHellman + ?Sized,
+{
+    let shared_secret = Zeroizing::new(secret_key.diffie_hellman(public_key));
+    SalsaBox::from_precomputed(PrecomputedKey(hsalsa_derive(rounds, &shared_secret)))
+}
+
+/// Create a [`ChaChaBox`] whose key is derived using
+/// [`hchacha_derive`] with the given number of rounds.
+pub fn chacha_box<S>(rounds: usize, public_key: &PublicKey, secret_key: &S) -> ChaChaBox
+where
+    S: DiffieHellman + ?Sized,
+{
+    let shared_secret = Zeroizing::new(secret_key.diffie_hellman(public_key));
+    let key = Zeroizing::new(hchacha_derive(rounds, &shared_secret));
+    ChaChaBox::from_key(GenericArray::from_slice(&key[..]))
+}
+
+fn salsa_quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[b] ^= state[a].wrapping_add(state[d]).rotate_left(7);
+    state[c] ^= state[b].wrapping_add(state[a]).rotate_left(9);
+    state[d] ^= state[c].wrapping_add(state[b]).rotate_left(13);
+    state[a] ^= state[d].wrapping_add(state[c]).rotate_left(18);
+}
+
+fn chacha_quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+fn load_words(bytes: &[u8; KEY_SIZE]) -> Zeroizing<[u32; 8]> {
+    let mut words = Zeroizing::new([0u32; 8]);
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(chunk);
+        *word = u32::from_le_bytes(buf);
+    }
+    words
+}
+
+fn store_words(words: &[u32; 8]) -> [u8; KEY_SIZE] {
+    let mut bytes = [0u8; KEY_SIZE];
+    for (chunk, word) in bytes.chunks_exact_mut(4).zip(words.iter()) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    bytes
+}
@@ -0,0 +1,96 @@
+//! Hierarchical deterministic key derivation, as specified by [SLIP-0010]
+//! for Curve25519.
+//!
+//! Only hardened derivation is defined for this curve: every index of a
+//! derivation path must be hardened, i.e. written with a `'` or `H` suffix
+//! (e.g. `m/44'/0'/1H`).
+//!
+//! [SLIP-0010]: https://github.com/satoshilabs/slips/blob/master/slip-0010.md
+
+use crate::{Error, SecretKey, KEY_SIZE};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use zeroize::{Zeroize, Zeroizing};
+
+/// HMAC key used to derive the master key from the seed.
+const MASTER_KEY: &[u8] = b"curve25519 seed";
+
+/// Offset of hardened indexes.
+const HARDENED_OFFSET: u32 = 1 << 31;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "hd")))]
+impl SecretKey {
+    /// Derive a [`SecretKey`] from a master seed along the given [SLIP-0010]
+    /// derivation path, e.g. `m/0'/1'`.
+    ///
+    /// Returns [`Error::InvalidDerivationPath`] if the path is malformed, or
+    /// if it contains a non-hardened index: SLIP-0010 only supports hardened
+    /// derivation for Curve25519.
+    ///
+    /// [SLIP-0010]: https://github.com/satoshilabs/slips/blob/master/slip-0010.md
+    pub fn derive_path(master_seed: &[u8], path: &str) -> Result<Self, Error> {
+        let mut segments = path.split('/');
+        if segments.next() != Some("m") {
+            return Err(Error::InvalidDerivationPath);
+        }
+
+        let mut node = Node::derive(MASTER_KEY, &[master_seed]);
+        for segment in segments {
+            let index = parse_hardened_index(segment).ok_or(Error::InvalidDerivationPath)?;
+            node = Node::derive(
+                &node.chain_code[..],
+                &[&[0], &node.key[..], &index.to_be_bytes()],
+            );
+        }
+
+        Ok(SecretKey(*node.key))
+    }
+}
+
+/// Key and chain code of a node of the derivation tree.
+struct Node {
+    key: Zeroizing<[u8; KEY_SIZE]>,
+    chain_code: Zeroizing<[u8; KEY_SIZE]>,
+}
+
+impl Node {
+    /// Derive a node by computing HMAC-SHA512 over the concatenated data.
+    fn derive(hmac_key: &[u8], data: &[&[u8]]) -> Self {
+        let mut mac =
+            Hmac::<Sha512>::new_from_slice(hmac_key).expect("HMAC accepts keys of any length");
+        for chunk in data {
+            mac.update(chunk);
+        }
+        let mut output = mac.finalize().into_bytes();
+
+        let mut node = Node {
+            key: Zeroizing::new([0u8; KEY_SIZE]),
+            chain_code: Zeroizing::new([0u8; KEY_SIZE]),
+        };
+        node.key.copy_from_slice(&output[..KEY_SIZE]);
+        node.chain_code.copy_from_slice(&output[KEY_SIZE..]);
+        output.as_mut_slice().zeroize();
+        node
+    }
+}
+
+/// Parse a hardened derivation path index, such as `0'` or `1H`.
+fn parse_hardened_index(segment: &str) -> Option<u32> {
+    let index = segment
+        .strip_suffix('\'')
+        .or_else(|| segment.strip_suffix('H'))?;
+
+    // Only allow canonical decimal indexes, without signs or leading zeros
+    if index.is_empty()
+        || !index.bytes().all(|b| b.is_ascii_digit())
+        || (index.len() > 1 && index.starts_with('0'))
+    {
+        return None;
+    }
+
+    index
+        .parse::<u32>()
+        .ok()
+        .filter(|&index| index < HARDENED_OFFSET)
+        .map(|index| index | HARDENED_OFFSET)
+}
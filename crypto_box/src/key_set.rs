@@ -0,0 +1,63 @@
+//! Sets of pinned public keys.
+
+use crate::PublicKey;
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+use subtle::{Choice, ConstantTimeEq};
+
+/// Set of trusted [`PublicKey`]s, e.g. for pinning peer keys.
+///
+/// Membership is checked with [`PublicKeySet::contains_ct`], which compares
+/// the queried key against every key in the set in constant time, so which
+/// key (if any) matched isn't leaked through timing. Only the number of keys
+/// in the set is observable.
+#[derive(Clone, Debug, Default)]
+pub struct PublicKeySet {
+    keys: Vec<PublicKey>,
+}
+
+impl PublicKeySet {
+    /// Create a new empty [`PublicKeySet`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a key to the set.
+    pub fn insert(&mut self, public_key: PublicKey) {
+        self.keys.push(public_key);
+    }
+
+    /// Number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Is the set empty?
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Check whether the given key is in the set, in constant time.
+    ///
+    /// Every key in the set is compared, without short-circuiting on the
+    /// first match.
+    pub fn contains_ct(&self, public_key: &PublicKey) -> Choice {
+        self.keys.iter().fold(Choice::from(0), |found, key| {
+            found | key.as_bytes().ct_eq(public_key.as_bytes())
+        })
+    }
+}
+
+impl FromIterator<PublicKey> for PublicKeySet {
+    fn from_iter<I: IntoIterator<Item = PublicKey>>(iter: I) -> Self {
+        PublicKeySet {
+            keys: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<PublicKey> for PublicKeySet {
+    fn extend<I: IntoIterator<Item = PublicKey>>(&mut self, iter: I) {
+        self.keys.extend(iter);
+    }
+}
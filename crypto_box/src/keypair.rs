@@ -0,0 +1,99 @@
+//! Keypairs with a cached public key.
+
+use crate::{Error, PublicKey, SecretKey, KEY_SIZE};
+use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+/// A [`SecretKey`] with its related [`PublicKey`].
+///
+/// The public key is computed once when the [`KeyPair`] is constructed, and
+/// then cached.
+#[derive(Clone, Debug)]
+pub struct KeyPair {
+    secret: SecretKey,
+    public: PublicKey,
+}
+
+impl KeyPair {
+    /// Generate a new random [`KeyPair`].
+    pub fn generate<T>(csprng: &mut T) -> Self
+    where
+        T: RngCore + CryptoRng,
+    {
+        Self::from_secret(SecretKey::generate(csprng))
+    }
+
+    /// Create a [`KeyPair`] from a [`SecretKey`], computing its public key.
+    pub fn from_secret(secret: SecretKey) -> Self {
+        let public = secret.public_key();
+        KeyPair { secret, public }
+    }
+
+    /// Decode a [`KeyPair`] from 64 bytes laid out as `secret || public`,
+    /// i.e. the 32-byte secret key followed by the 32-byte public key.
+    ///
+    /// Returns [`Error::PublicKeyMismatch`] if the public key isn't the one
+    /// derived from the secret key, e.g. because the bytes are corrupted.
+    pub fn from_bytes(bytes: &[u8; 2 * KEY_SIZE]) -> Result<Self, Error> {
+        let mut secret = SecretKey([0u8; KEY_SIZE]);
+        secret.0.copy_from_slice(&bytes[..KEY_SIZE]);
+        let keypair = KeyPair::from_secret(secret);
+
+        if keypair.public.as_bytes()[..] != bytes[KEY_SIZE..] {
+            return Err(Error::PublicKeyMismatch);
+        }
+
+        Ok(keypair)
+    }
+
+    /// Encode this [`KeyPair`] as 64 bytes laid out as `secret || public`
+    /// (see [`KeyPair::from_bytes`]).
+    pub fn to_bytes(&self) -> Zeroizing<[u8; 2 * KEY_SIZE]> {
+        let mut bytes = Zeroizing::new([0u8; 2 * KEY_SIZE]);
+        bytes[..KEY_SIZE].copy_from_slice(self.secret.as_bytes());
+        bytes[KEY_SIZE..].copy_from_slice(self.public.as_bytes());
+        bytes
+    }
+
+    /// Get the contained [`PublicKey`].
+    pub fn public(&self) -> &PublicKey {
+        &self.public
+    }
+
+    /// Get the contained [`SecretKey`].
+    pub fn secret(&self) -> &SecretKey {
+        &self.secret
+    }
+
+    /// Consume the [`KeyPair`] to extract the contained [`PublicKey`] &
+    /// [`SecretKey`].
+    pub fn split(self) -> (PublicKey, SecretKey) {
+        (self.public, self.secret)
+    }
+}
+
+impl From<SecretKey> for KeyPair {
+    fn from(secret: SecretKey) -> KeyPair {
+        KeyPair::from_secret(secret)
+    }
+}
+
+/// Generate a new random keypair directly into the given buffers, without
+/// constructing an owned [`SecretKey`] or [`PublicKey`].
+///
+/// This gives control over where the secret key material lives, e.g. in a
+/// dedicated memory region. The buffers can later be turned into keys with
+/// [`SecretKey::from`] and [`PublicKey::from`].
+pub fn generate_keypair_into<T>(
+    csprng: &mut T,
+    secret_out: &mut [u8; KEY_SIZE],
+    public_out: &mut [u8; KEY_SIZE],
+) where
+    T: RngCore + CryptoRng,
+{
+    csprng.fill_bytes(secret_out);
+
+    // See `SecretKey::public_key`: `StaticSecret` zeroizes its copy on drop
+    let secret = x25519_dalek::StaticSecret::from(*secret_out);
+    *public_out = *x25519_dalek::PublicKey::from(&secret).as_bytes();
+}
@@ -105,6 +105,11 @@
 //! Currently, `crypto_box::Box` is default to use `xsalsa20poly1305` which doesn't support non-empty associated data
 //! field. To specify customized AD, you can use `crypto_box::ChaChaBox` instead.
 //!
+//! The two boxes are not interchangeable: they derive their keys from the
+//! X25519 shared secret with different functions (HSalsa20 vs HChaCha20), and
+//! use different ciphers, so a message encrypted with one box fails to
+//! authenticate with the other, even for the same keypair.
+//!
 //! ```rust
 //! # #[cfg(feature = "std")]
 //! # {
@@ -191,23 +196,140 @@
 #![warn(missing_docs, rust_2018_idioms)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(all(feature = "hazmat-reduced", not(crypto_box_hazmat_reduced)))]
+compile_error!(
+    "the `hazmat-reduced` feature is insecure and also requires building with \
+     `RUSTFLAGS=\"--cfg crypto_box_hazmat_reduced\"`"
+);
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+mod aad;
+mod backend;
+mod builder;
+mod channel_binding;
+mod keypair;
+mod nonce;
+mod scratch;
+mod suite;
+
+#[cfg(feature = "serde")]
+mod base64;
+
+#[cfg(feature = "hkdf")]
+mod duplex;
+
+#[cfg(feature = "hd")]
+mod hd;
+
+#[cfg(feature = "hkdf")]
+mod subkeys;
+
+#[cfg(feature = "alloc")]
+mod key_set;
+#[cfg(feature = "alloc")]
+mod message;
+#[cfg(feature = "alloc")]
+mod replay;
+
+#[cfg(feature = "ed25519")]
+mod ed25519;
+
+#[cfg(feature = "mlock")]
+mod mlock;
+
+#[cfg(feature = "blake2")]
+mod seal;
+
+#[cfg(feature = "z85")]
+mod z85;
+
+#[cfg(feature = "compat")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compat")))]
+pub mod compat;
+
+pub mod errors;
+
+pub mod hazmat;
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod pad;
+
+pub mod primitives;
+
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub mod stream;
+
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+
 pub use rand_core;
 pub use xsalsa20poly1305::{aead, generate_nonce};
 
-use chacha20::hchacha;
-use chacha20poly1305::XChaCha20Poly1305;
+pub use crate::{
+    backend::{backend_info, BackendInfo},
+    builder::{BoxBuilder, NonceStrategy, NoncedBox},
+    channel_binding::ChannelBoundBox,
+    errors::Error,
+    keypair::{generate_keypair_into, KeyPair},
+    nonce::{nonce_with_fixed_context, Endian, NonceCounter},
+    scratch::ScratchBuffer,
+    suite::{supported_suites, Suite},
+};
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub use crate::{key_set::PublicKeySet, message::EncryptedMessage, replay::ReplayWindow};
+
+#[cfg(feature = "hkdf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hkdf")))]
+pub use crate::{
+    duplex::DuplexKeys,
+    subkeys::{EncKey, MacKey},
+};
+
+#[cfg(feature = "mlock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mlock")))]
+pub use crate::mlock::LockedSecretKey;
+
+#[cfg(feature = "blake2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blake2")))]
+pub use crate::seal::SealedBox;
+
+use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305};
 use core::fmt::{self, Debug};
+use poly1305::{universal_hash::NewUniversalHash, Poly1305};
 use rand_core::{CryptoRng, RngCore};
-use salsa20::hsalsa20;
+use salsa20::{
+    cipher::{NewCipher, StreamCipher},
+    XSalsa20,
+};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 use x25519_dalek::{x25519, X25519_BASEPOINT_BYTES};
 use xsalsa20poly1305::aead::{
-    consts::{U0, U16, U24},
-    generic_array::GenericArray,
-    AeadCore, AeadInPlace, Buffer, Error, NewAead,
+    consts::{U0, U12, U16, U24, U32},
+    generic_array::{typenum::Unsigned, GenericArray},
+    AeadCore, AeadInPlace, Buffer, NewAead,
 };
 use xsalsa20poly1305::XSalsa20Poly1305;
 use zeroize::{Zeroize, Zeroizing};
 
+#[cfg(feature = "alloc")]
+use {
+    alloc::vec::Vec,
+    xsalsa20poly1305::aead::{Aead, Payload},
+};
+
+#[cfg(feature = "heapless")]
+use xsalsa20poly1305::aead::heapless;
+
 #[cfg(feature = "serde")]
 use serde_crate::{
     de::{Deserialize, Deserializer},
@@ -217,12 +339,250 @@ use serde_crate::{
 /// Size of a `crypto_box` public or secret key in bytes.
 pub const KEY_SIZE: usize = 32;
 
+/// Size of a Poly1305 tag in bytes.
+const TAG_SIZE: usize = 16;
+
+/// Size of the length prefix of contexts bound by
+/// [`SalsaBox::encrypt_with_context`].
+#[cfg(feature = "alloc")]
+const CONTEXT_LEN_SIZE: usize = 8;
+
+/// Size of the length prefix of records decrypted by
+/// [`SalsaBox::decrypt_records`].
+#[cfg(feature = "alloc")]
+const RECORD_LEN_SIZE: usize = 4;
+
+/// Size of the random nonce prefix of blobs produced by
+/// [`SalsaBox::encrypt_large`].
+#[cfg(feature = "alloc")]
+const LARGE_NONCE_PREFIX_SIZE: usize = 19;
+
+/// BLAKE2b personalization used by [`SalsaBox::key_confirmation`].
+#[cfg(feature = "blake2")]
+const KEY_CONFIRMATION_PERSONA: &[u8; 16] = b"crypto_box_kconf";
+
+/// BLAKE2b personalization used by [`SalsaBox::key_id`].
+#[cfg(feature = "blake2")]
+const KEY_ID_PERSONA: &[u8; 16] = b"crypto_box_keyid";
+
+/// Domain separation label for [`channel_id`].
+#[cfg(feature = "blake2")]
+const CHANNEL_ID_LABEL: &[u8] = b"crypto_box channel id";
+
+/// Domain separation label for the key commitments of
+/// [`ChaChaBox::encrypt_committing`].
+#[cfg(all(feature = "alloc", feature = "blake2"))]
+const COMMITMENT_LABEL: &[u8] = b"crypto_box key commitment";
+
+/// BLAKE2b personalization used by [`ChaChaBox::encrypt_siv`].
+#[cfg(all(feature = "alloc", feature = "blake2"))]
+const SIV_PERSONA: &[u8; 16] = b"crypto_box_siv\0\0";
+
+/// BLAKE2b personalization used to derive the key of the synthetic nonce
+/// PRF of [`ChaChaBox::encrypt_siv`] from the box's key.
+#[cfg(all(feature = "alloc", feature = "blake2"))]
+const SIV_KEY_PERSONA: &[u8; 16] = b"crypto_box_sivk\0";
+
+/// BLAKE2b personalization used to derive the key committed to by
+/// [`ChaChaBox::encrypt_committing`] from the box's key.
+#[cfg(all(feature = "alloc", feature = "blake2"))]
+const COMMITMENT_KEY_PERSONA: &[u8; 16] = b"crypto_box_cmtk\0";
+
+/// Size of the key commitments of [`ChaChaBox::encrypt_committing`].
+#[cfg(all(feature = "alloc", feature = "blake2"))]
+const COMMITMENT_SIZE: usize = 32;
+
+/// HKDF info label used by [`SalsaBox::rekey`].
+#[cfg(feature = "hkdf")]
+const REKEY_INFO: &[u8] = b"crypto_box rekey";
+
+/// HKDF info labels used by [`DuplexKeys::derive`] for the keys used to send
+/// messages from the initiator and from the responder, respectively.
+#[cfg(feature = "hkdf")]
+const DUPLEX_INFO: [&[u8]; 2] = [
+    b"crypto_box duplex initiator",
+    b"crypto_box duplex responder",
+];
+
+/// HKDF info labels used by [`SalsaBox::derive_enc_mac_keys`] for the
+/// encryption and authentication keys, respectively.
+#[cfg(feature = "hkdf")]
+const ENC_MAC_INFO: [&[u8]; 2] = [b"crypto_box enc key", b"crypto_box mac key"];
+
+/// Nonce for use with [`SalsaBox`] and [`ChaChaBox`].
+///
+/// Implemented as an alias for [`GenericArray`]. A plain `[u8; 24]` array
+/// can be converted into a [`Nonce`] using [`From`].
+pub type Nonce = GenericArray<u8, U24>;
+
 /// Poly1305 tag.
 ///
 /// Implemented as an alias for [`GenericArray`].
 pub type Tag = GenericArray<u8, U16>;
 
+/// 96-bit IETF nonce used by [`IetfChaChaBox`].
+pub type IetfNonce = GenericArray<u8, U12>;
+
+/// Generate a random nonce as a plain array, for callers which prefer to
+/// avoid [`GenericArray`] in their own signatures.
+///
+/// The array can be converted back into a [`Nonce`] using [`From`].
+pub fn generate_nonce_array<T>(csprng: &mut T) -> [u8; 24]
+where
+    T: RngCore + CryptoRng,
+{
+    let mut nonce = [0u8; 24];
+    csprng.fill_bytes(&mut nonce);
+    nonce
+}
+
+/// Compare two Poly1305 tags in constant time, for protocols which carry
+/// and verify tags themselves.
+///
+/// Unlike `==`, the comparison doesn't short-circuit on the first differing
+/// byte, so it doesn't leak how much of a forged tag was correct.
+pub fn verify_tag(expected: &Tag, actual: &Tag) -> bool {
+    expected.ct_eq(actual).into()
+}
+
+/// Re-encrypt a ciphertext from one box to another, e.g. to give a new
+/// recipient access to a stored object.
+///
+/// The ciphertext is decrypted with `old_box` under `old_nonce`, then
+/// encrypted with `new_box` under `new_nonce`. This happens in a single
+/// buffer, which is zeroized if either step fails, so the plaintext is never
+/// left behind in memory.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn rewrap(
+    old_box: &SalsaBox,
+    old_nonce: &Nonce,
+    ciphertext: &[u8],
+    new_box: &SalsaBox,
+    new_nonce: &Nonce,
+) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::with_capacity(ciphertext.len());
+    buffer.extend_from_slice(ciphertext);
+
+    let result = old_box
+        .decrypt_in_place(old_nonce, b"", &mut buffer)
+        .and_then(|_| new_box.encrypt_in_place(new_nonce, b"", &mut buffer));
+
+    if let Err(err) = result {
+        buffer.zeroize();
+        return Err(err.into());
+    }
+
+    Ok(buffer)
+}
+
+/// Compute a channel identifier from the public keys of both parties.
+///
+/// The keys are sorted before being hashed with BLAKE2b-256, so both parties
+/// compute the same identifier regardless of argument order.
+#[cfg(feature = "blake2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blake2")))]
+pub fn channel_id(a: &PublicKey, b: &PublicKey) -> [u8; 32] {
+    use blake2::{digest::Digest, Blake2b};
+
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+
+    let mut hasher = Blake2b::<U32>::new();
+    hasher.update(CHANNEL_ID_LABEL);
+    hasher.update(first.as_bytes());
+    hasher.update(second.as_bytes());
+    hasher.finalize().into()
+}
+
+/// X25519 Diffie-Hellman key agreement with a secret key.
+///
+/// This is implemented by [`SecretKey`], and can also be implemented for
+/// secret keys which aren't held in memory (e.g. in an HSM), allowing boxes
+/// to be constructed without exposing the secret scalar.
+pub trait DiffieHellman {
+    /// Perform X25519 Diffie-Hellman between this secret key and the given
+    /// [`PublicKey`], returning the raw shared secret.
+    fn diffie_hellman(&self, public_key: &PublicKey) -> [u8; KEY_SIZE];
+
+    /// Get the [`PublicKey`] which corresponds to this secret key.
+    fn public_key(&self) -> PublicKey {
+        PublicKey(self.diffie_hellman(&PublicKey(X25519_BASEPOINT_BYTES)))
+    }
+}
+
+/// Sizes of the nonces and tags of a box, as plain `usize` values.
+///
+/// This is implemented for every [`AeadCore`], and saves generic code from
+/// converting its associated type-level sizes itself.
+pub trait BoxExt: AeadCore {
+    /// Size of this box's nonces, in bytes.
+    fn nonce_size() -> usize {
+        Self::NonceSize::to_usize()
+    }
+
+    /// Size of this box's authentication tags, in bytes.
+    fn tag_size() -> usize {
+        Self::TagSize::to_usize()
+    }
+}
+
+impl<T: AeadCore> BoxExt for T {}
+
+/// Clamp an X25519 scalar, as per RFC 7748.
+fn clamp(scalar: &mut [u8; KEY_SIZE]) {
+    scalar[0] &= 248;
+    scalar[KEY_SIZE - 1] &= 127;
+    scalar[KEY_SIZE - 1] |= 64;
+}
+
+/// Encodings of X25519 points of small order (and of their non-canonical
+/// equivalents), as blacklisted by libsodium.
+const SMALL_ORDER_BLACKLIST: [[u8; KEY_SIZE]; 7] = [
+    // 0 (order 4)
+    [0; KEY_SIZE],
+    // 1 (order 1)
+    [
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ],
+    // order 8
+    [
+        0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f, 0xc4,
+        0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16, 0x5f, 0x49,
+        0xb8, 0x00,
+    ],
+    // order 8
+    [
+        0x5f, 0x9c, 0x95, 0xbc, 0xa3, 0x50, 0x8c, 0x24, 0xb1, 0xd0, 0xb1, 0x55, 0x9c, 0x83, 0xef,
+        0x5b, 0x04, 0x44, 0x5c, 0xc4, 0x58, 0x1c, 0x8e, 0x86, 0xd8, 0x22, 0x4e, 0xdd, 0xd0, 0x9f,
+        0x11, 0x57,
+    ],
+    // p - 1 (order 2)
+    [
+        0xec, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+    // p (=0, order 4)
+    [
+        0xed, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+    // p + 1 (=1, order 1)
+    [
+        0xee, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+];
+
 /// A `crypto_box` secret key.
+///
+/// The key bytes are zeroized when the key is dropped. They can also be
+/// zeroized on demand using [`Zeroize::zeroize`], after which the key is the
+/// all-zero scalar and is no longer usable.
 #[derive(Clone)]
 pub struct SecretKey([u8; KEY_SIZE]);
 
@@ -237,9 +597,113 @@ impl SecretKey {
         SecretKey(bytes)
     }
 
+    /// Generate a random [`SecretKey`], propagating failures of the random
+    /// number generator using [`RngCore::try_fill_bytes`].
+    ///
+    /// Unlike [`SecretKey::generate`], this allows handling entropy sources
+    /// which can fail, such as hardware TRNGs on embedded platforms.
+    pub fn try_generate<T>(csprng: &mut T) -> Result<Self, rand_core::Error>
+    where
+        T: RngCore + CryptoRng,
+    {
+        let mut secret_key = SecretKey([0u8; KEY_SIZE]);
+        csprng.try_fill_bytes(&mut secret_key.0)?;
+        Ok(secret_key)
+    }
+
+    /// Generate a random [`SecretKey`] whose bytes are already clamped.
+    ///
+    /// X25519 clamps scalars before use (clearing the 3 lowest bits and the
+    /// highest bit, and setting the second-highest bit), so keys differing
+    /// only in those bits are equivalent. Keys generated by this method are
+    /// stored in their clamped form, so that their bytes are canonical.
+    ///
+    /// If `reject_degenerate` is set, keys whose clamped top byte is exactly
+    /// `0x40` (i.e. none of the random bits 248 to 253 are set, which happens
+    /// with probability 1/64) are also rejected, and a new key is drawn.
+    pub fn generate_clamped<T>(csprng: &mut T, reject_degenerate: bool) -> Self
+    where
+        T: RngCore + CryptoRng,
+    {
+        loop {
+            let mut secret_key = Self::generate(csprng);
+            clamp(&mut secret_key.0);
+
+            if !reject_degenerate || secret_key.0[KEY_SIZE - 1] != 0x40 {
+                return secret_key;
+            }
+        }
+    }
+
+    /// Generate a [`SecretKey`] from an entropy source given as a function
+    /// which fills the provided buffer, e.g. a hardware RNG peripheral.
+    ///
+    /// The function must fill the whole buffer with uniformly random bytes
+    /// from a cryptographically secure source.
+    pub fn from_entropy<F>(mut fill_bytes: F) -> Self
+    where
+        F: FnMut(&mut [u8]),
+    {
+        let mut secret_key = SecretKey([0u8; KEY_SIZE]);
+        fill_bytes(&mut secret_key.0);
+        secret_key
+    }
+
+    /// Generate a [`SecretKey`] reproducibly from a [`ChaCha20Rng`] seeded
+    /// with the given seed, e.g. for deterministic simulations and tests.
+    ///
+    /// This draws the key from the RNG exactly like [`SecretKey::generate`]:
+    /// it is not a key derivation function, and the seed must be kept as
+    /// secret as the key itself.
+    ///
+    /// [`ChaCha20Rng`]: rand_chacha::ChaCha20Rng
+    #[cfg(feature = "rand_chacha")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand_chacha")))]
+    pub fn from_rng_seed(seed: [u8; 32]) -> Self {
+        use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+        Self::generate(&mut ChaCha20Rng::from_seed(seed))
+    }
+
+    /// Generate a random [`SecretKey`] along with a random [`Nonce`].
+    ///
+    /// The key and nonce are independent halves of a single draw from the
+    /// provided RNG, which saves an RNG invocation on platforms where these
+    /// are expensive.
+    pub fn generate_with_nonce<T>(csprng: &mut T) -> (Self, Nonce)
+    where
+        T: RngCore + CryptoRng,
+    {
+        let mut bytes = [0u8; KEY_SIZE + 24];
+        csprng.fill_bytes(&mut bytes);
+
+        let mut secret_key = SecretKey([0u8; KEY_SIZE]);
+        secret_key.0.copy_from_slice(&bytes[..KEY_SIZE]);
+        let nonce = Nonce::clone_from_slice(&bytes[KEY_SIZE..]);
+        bytes.zeroize();
+
+        (secret_key, nonce)
+    }
+
     /// Get the [`PublicKey`] which corresponds to this [`SecretKey`]
+    ///
+    /// This is computed in constant time, using the fixed-base scalar
+    /// multiplication of [`x25519_dalek`].
     pub fn public_key(&self) -> PublicKey {
-        PublicKey(x25519(self.0, X25519_BASEPOINT_BYTES))
+        // `x25519_dalek::PublicKey::from(&StaticSecret)` multiplies by the
+        // basepoint using a precomputed table, in constant time with respect
+        // to the secret scalar (as does the Montgomery ladder used by the
+        // `x25519` function, but the fixed-base path is also faster).
+        let secret = x25519_dalek::StaticSecret::from(self.0);
+        PublicKey(*x25519_dalek::PublicKey::from(&secret).as_bytes())
+    }
+
+    /// Check whether the given [`PublicKey`] is the one corresponding to this
+    /// [`SecretKey`], e.g. to catch misconfigured key pairs at startup.
+    ///
+    /// The public key is recomputed and compared in constant time.
+    pub fn matches_public(&self, public_key: &PublicKey) -> bool {
+        self.public_key().0.ct_eq(&public_key.0).into()
     }
 
     #[deprecated(note = "use `as_bytes` instead")]
@@ -252,6 +716,17 @@ impl SecretKey {
     pub fn as_bytes(&self) -> &[u8; KEY_SIZE] {
         &self.0
     }
+
+    /// Copy the raw [`SecretKey`] bytes into a buffer owned by the caller,
+    /// e.g. for provisioning a hardware security module.
+    ///
+    /// This is a sensitive escape hatch: no intermediate copies are made,
+    /// but the caller is responsible for zeroizing `out` once done with it
+    /// (e.g. using [`Zeroize`]). Prefer keeping keys inside a [`SecretKey`],
+    /// which is zeroized on drop, whenever possible.
+    pub fn export_into(&self, out: &mut [u8; KEY_SIZE]) {
+        out.copy_from_slice(&self.0);
+    }
 }
 
 impl From<[u8; KEY_SIZE]> for SecretKey {
@@ -260,22 +735,61 @@ impl From<[u8; KEY_SIZE]> for SecretKey {
     }
 }
 
+impl From<&[u8; KEY_SIZE]> for SecretKey {
+    fn from(bytes: &[u8; KEY_SIZE]) -> SecretKey {
+        SecretKey(*bytes)
+    }
+}
+
+#[cfg(feature = "dalek")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dalek")))]
+impl From<x25519_dalek::StaticSecret> for SecretKey {
+    fn from(secret: x25519_dalek::StaticSecret) -> SecretKey {
+        SecretKey(secret.to_bytes())
+    }
+}
+
+/// Convert a [`SecretKey`] into an [`x25519_dalek::StaticSecret`] for use
+/// with lower-level dalek APIs, without going through the raw key bytes.
+#[cfg(feature = "dalek")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dalek")))]
+impl From<&SecretKey> for x25519_dalek::StaticSecret {
+    fn from(secret_key: &SecretKey) -> x25519_dalek::StaticSecret {
+        x25519_dalek::StaticSecret::from(secret_key.0)
+    }
+}
+
+impl DiffieHellman for SecretKey {
+    fn diffie_hellman(&self, public_key: &PublicKey) -> [u8; KEY_SIZE] {
+        x25519(self.0, public_key.0)
+    }
+}
+
 impl Debug for SecretKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str("SecretKey(...)")
     }
 }
 
+impl Zeroize for SecretKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl Drop for SecretKey {
     fn drop(&mut self) {
-        self.0.zeroize();
+        self.zeroize();
     }
 }
 
 /// A `crypto_box` public key.
 ///
-/// This type can be serialized if the `serde` feature is enabled.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+/// This type can be serialized if the `serde` feature is enabled: as a
+/// Base64 string in human-readable formats (e.g. JSON), and as bytes
+/// otherwise. Neither requires `alloc`, so keys can be (de)serialized with
+/// `no_std` formats such as `postcard`.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct PublicKey([u8; KEY_SIZE]);
 
 impl PublicKey {
@@ -283,6 +797,61 @@ impl PublicKey {
     pub fn as_bytes(&self) -> &[u8; KEY_SIZE] {
         &self.0
     }
+
+    /// Check in constant time that this [`PublicKey`] isn't the encoding of
+    /// one of the known points of small order.
+    ///
+    /// Diffie-Hellman with such a point results in an all-zero shared secret
+    /// regardless of the secret key. The blacklist is the one used by
+    /// libsodium, and as in libsodium the top bit of the encoding is ignored.
+    pub fn is_valid_ct(&self) -> Choice {
+        let mut small_order = Choice::from(0);
+
+        for point in SMALL_ORDER_BLACKLIST.iter() {
+            let mut diff = (self.0[KEY_SIZE - 1] & 0x7f) ^ point[KEY_SIZE - 1];
+            for (a, b) in self.0[..KEY_SIZE - 1].iter().zip(point.iter()) {
+                diff |= a ^ b;
+            }
+            small_order |= diff.ct_eq(&0);
+        }
+
+        !small_order
+    }
+
+    /// Parse a [`PublicKey`] from a string containing 64 hex digits.
+    ///
+    /// Both lowercase and uppercase digits are accepted.
+    pub fn from_hex(hex: &str) -> Result<Self, Error> {
+        let hex = hex.as_bytes();
+
+        if hex.len() & 1 == 1 {
+            return Err(Error::OddLength);
+        }
+
+        if hex.len() != KEY_SIZE * 2 {
+            return Err(Error::InvalidLength {
+                expected: KEY_SIZE * 2,
+                got: hex.len(),
+            });
+        }
+
+        let mut bytes = [0u8; KEY_SIZE];
+        for (byte, digits) in bytes.iter_mut().zip(hex.chunks_exact(2)) {
+            *byte = (decode_hex_digit(digits[0])? << 4) | decode_hex_digit(digits[1])?;
+        }
+
+        Ok(PublicKey(bytes))
+    }
+}
+
+/// Decode a single ASCII hex digit.
+fn decode_hex_digit(digit: u8) -> Result<u8, Error> {
+    match digit {
+        b'0'..=b'9' => Ok(digit - b'0'),
+        b'a'..=b'f' => Ok(digit - b'a' + 10),
+        b'A'..=b'F' => Ok(digit - b'A' + 10),
+        _ => Err(Error::InvalidHexCharacter),
+    }
 }
 
 impl AsRef<[u8]> for PublicKey {
@@ -291,6 +860,14 @@ impl AsRef<[u8]> for PublicKey {
     }
 }
 
+/// Public keys aren't secret, but this allows them to be scrubbed along with
+/// secret keys, e.g. on session teardown.
+impl Zeroize for PublicKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl From<&SecretKey> for PublicKey {
     fn from(secret_key: &SecretKey) -> PublicKey {
         secret_key.public_key()
@@ -303,6 +880,28 @@ impl From<[u8; KEY_SIZE]> for PublicKey {
     }
 }
 
+impl From<&[u8; KEY_SIZE]> for PublicKey {
+    fn from(bytes: &[u8; KEY_SIZE]) -> PublicKey {
+        PublicKey(*bytes)
+    }
+}
+
+#[cfg(feature = "dalek")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dalek")))]
+impl From<x25519_dalek::PublicKey> for PublicKey {
+    fn from(public_key: x25519_dalek::PublicKey) -> PublicKey {
+        PublicKey(public_key.to_bytes())
+    }
+}
+
+#[cfg(feature = "dalek")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dalek")))]
+impl From<&PublicKey> for x25519_dalek::PublicKey {
+    fn from(public_key: &PublicKey) -> x25519_dalek::PublicKey {
+        x25519_dalek::PublicKey::from(public_key.0)
+    }
+}
+
 #[cfg(feature = "serde")]
 #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
 impl Serialize for PublicKey {
@@ -310,7 +909,12 @@ impl Serialize for PublicKey {
     where
         S: Serializer,
     {
-        serializer.serialize_bytes(&self.0)
+        if serializer.is_human_readable() {
+            let encoded = base64::encode(&self.0);
+            serializer.serialize_str(core::str::from_utf8(&encoded).expect("base64 is ASCII"))
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
     }
 }
 
@@ -322,7 +926,7 @@ impl<'de> Deserialize<'de> for PublicKey {
         D: Deserializer<'de>,
     {
         use core::convert::TryInto;
-        use serde_crate::de::{Error, SeqAccess, Visitor};
+        use serde_crate::de::{Error, SeqAccess, Unexpected, Visitor};
 
         struct PublicKeyVisitor;
 
@@ -338,11 +942,11 @@ impl<'de> Deserialize<'de> for PublicKey {
                 S: SeqAccess<'de>,
             {
                 let mut key_bytes = [0; KEY_SIZE];
-                for i in 0..KEY_SIZE {
-                    key_bytes[i] = match seq.next_element()? {
+                for (i, byte) in key_bytes.iter_mut().enumerate() {
+                    *byte = match seq.next_element()? {
                         Some(val) => val,
                         None => {
-                            return Err(Error::invalid_length(i - 1, &self));
+                            return Err(Error::invalid_length(i, &self));
                         }
                     }
                 }
@@ -359,136 +963,1559 @@ impl<'de> Deserialize<'de> for PublicKey {
                     .map_err(|_| Error::invalid_length(bytes.len(), &self))?;
                 Ok(PublicKey::from(array))
             }
-        }
-
-        deserializer.deserialize_bytes(PublicKeyVisitor)
-    }
-}
-
-macro_rules! impl_aead_in_place {
-    ($box:ty, $nonce_size:ty, $tag_size:ty, $ct_overhead:ty) => {
-        impl AeadCore for $box {
-            type NonceSize = $nonce_size;
-            type TagSize = $tag_size;
-            type CiphertextOverhead = $ct_overhead;
-        }
-
-        impl AeadInPlace for $box {
-            fn encrypt_in_place(
-                &self,
-                nonce: &GenericArray<u8, Self::NonceSize>,
-                associated_data: &[u8],
-                buffer: &mut dyn Buffer,
-            ) -> Result<(), Error> {
-                self.0.encrypt_in_place(nonce, associated_data, buffer)
-            }
-
-            fn encrypt_in_place_detached(
-                &self,
-                nonce: &GenericArray<u8, Self::NonceSize>,
-                associated_data: &[u8],
-                buffer: &mut [u8],
-            ) -> Result<Tag, Error> {
-                self.0
-                    .encrypt_in_place_detached(nonce, associated_data, buffer)
-            }
 
-            fn decrypt_in_place(
-                &self,
-                nonce: &GenericArray<u8, Self::NonceSize>,
-                associated_data: &[u8],
-                buffer: &mut dyn Buffer,
-            ) -> Result<(), Error> {
-                self.0.decrypt_in_place(nonce, associated_data, buffer)
+            fn visit_str<E>(self, encoded: &str) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                base64::decode(encoded.as_bytes())
+                    .map(PublicKey::from)
+                    .ok_or_else(|| Error::invalid_value(Unexpected::Str(encoded), &self))
             }
+        }
 
-            fn decrypt_in_place_detached(
-                &self,
-                nonce: &GenericArray<u8, Self::NonceSize>,
-                associated_data: &[u8],
-                buffer: &mut [u8],
-                tag: &Tag,
-            ) -> Result<(), Error> {
-                self.0
-                    .decrypt_in_place_detached(nonce, associated_data, buffer, tag)
-            }
+        // Human-readable formats also accept the sequence form which they
+        // used before keys were encoded as Base64 strings
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(PublicKeyVisitor)
+        } else {
+            deserializer.deserialize_bytes(PublicKeyVisitor)
         }
-    };
+    }
 }
 
-/// Alias for [`SalsaBox`].
-pub type Box = SalsaBox;
-
-/// Public-key encryption scheme based on the [X25519] Elliptic Curve
-/// Diffie-Hellman function and the [XSalsa20Poly1305] authenticated encryption
-/// cipher.
+/// A precomputed [`SalsaBox`] shared key.
 ///
-/// This type impls the [`aead::Aead`] trait, and otherwise functions as a
-/// symmetric Authenticated Encryption with Associated Data (AEAD) cipher
-/// once instantiated.
+/// This is the HSalsa20-derived key computed from the X25519 shared secret,
+/// i.e. the output of libsodium's `crypto_box_beforenm`.
 ///
-/// [X25519]: https://cr.yp.to/ecdh.html
-/// [XSalsa20Poly1305]: https://github.com/RustCrypto/AEADs/tree/master/xsalsa20poly1305
+/// This type can be serialized if the `serde` feature is enabled, in the same
+/// form as [`PublicKey`]. Note that doing so serializes sensitive key
+/// material.
 #[derive(Clone)]
-pub struct SalsaBox(XSalsa20Poly1305);
+pub struct PrecomputedKey([u8; KEY_SIZE]);
 
-impl SalsaBox {
-    /// Create a new [`SalsaBox`], performing X25519 Diffie-Hellman to derive
-    /// a shared secret from the provided public and secret keys.
-    pub fn new(public_key: &PublicKey, secret_key: &SecretKey) -> Self {
-        let shared_secret = Zeroizing::new(x25519(secret_key.0, public_key.0));
+impl PrecomputedKey {
+    /// Compute a new [`PrecomputedKey`], performing X25519 Diffie-Hellman to
+    /// derive a shared secret from the provided public and secret keys.
+    pub fn new<S>(public_key: &PublicKey, secret_key: &S) -> Self
+    where
+        S: DiffieHellman + ?Sized,
+    {
+        let shared_secret = Zeroizing::new(secret_key.diffie_hellman(public_key));
 
         // Use HSalsa20 to create a uniformly random key from the shared secret
-        let mut key = hsalsa20(
-            GenericArray::from_slice(&*shared_secret),
-            &GenericArray::default(),
-        );
+        PrecomputedKey(hazmat::hsalsa_derive(&shared_secret))
+    }
 
-        let cipher = XSalsa20Poly1305::new(&key);
-        key.zeroize();
+    /// Get a slice of the [`PrecomputedKey`] bytes
+    pub fn as_bytes(&self) -> &[u8; KEY_SIZE] {
+        &self.0
+    }
 
-        SalsaBox(cipher)
+    /// Select `a` if `choice` is 0, or `b` if `choice` is 1, in constant
+    /// time.
+    ///
+    /// This mirrors [`ConditionallySelectable::conditional_select`], which
+    /// can't be implemented because [`PrecomputedKey`] is not `Copy` (it is
+    /// zeroized on drop).
+    pub fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut key = PrecomputedKey([0u8; KEY_SIZE]);
+        for ((out, a), b) in key.0.iter_mut().zip(a.0.iter()).zip(b.0.iter()) {
+            *out = u8::conditional_select(a, b, choice);
+        }
+        key
     }
 }
 
-impl_aead_in_place!(SalsaBox, U24, U16, U0);
-
-/// Public-key encryption scheme based on the [X25519] Elliptic Curve
-/// Diffie-Hellman function and the [XChaCha20Poly1305] authenticated encryption
-/// cipher.
-///
-/// This type impls the [`aead::Aead`] trait, and otherwise functions as a
-/// symmetric Authenticated Encryption with Associated Data (AEAD) cipher
-/// once instantiated.
-///
-/// [X25519]: https://cr.yp.to/ecdh.html
-/// [XChaCha20Poly1305]: https://github.com/RustCrypto/AEADs/blob/master/chacha20poly1305/
-#[derive(Clone)]
-pub struct ChaChaBox(XChaCha20Poly1305);
-
-impl ChaChaBox {
-    /// Create a new [`ChaChaBox`], performing X25519 Diffie-Hellman to derive
-    /// a shared secret from the provided public and secret keys.
-    pub fn new(public_key: &PublicKey, secret_key: &SecretKey) -> Self {
-        let shared_secret = Zeroizing::new(x25519(secret_key.0, public_key.0));
-
-        // Use HChaCha20 to create a uniformly random key from the shared secret
-        let mut key = hchacha::<chacha20::R20>(
-            GenericArray::from_slice(&*shared_secret),
-            &GenericArray::default(),
-        );
-
-        let cipher = XChaCha20Poly1305::new(&key);
-        key.zeroize();
-
-        ChaChaBox(cipher)
+impl From<[u8; KEY_SIZE]> for PrecomputedKey {
+    fn from(bytes: [u8; KEY_SIZE]) -> PrecomputedKey {
+        PrecomputedKey(bytes)
+    }
+}
+
+impl AsRef<[u8; KEY_SIZE]> for PrecomputedKey {
+    fn as_ref(&self) -> &[u8; KEY_SIZE] {
+        &self.0
+    }
+}
+
+impl Debug for PrecomputedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PrecomputedKey(...)")
+    }
+}
+
+impl Drop for PrecomputedKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for PrecomputedKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            let encoded = Zeroizing::new(base64::encode(&self.0));
+            serializer.serialize_str(core::str::from_utf8(&*encoded).expect("base64 is ASCII"))
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for PrecomputedKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde_crate::de::{Error, SeqAccess, Unexpected, Visitor};
+
+        struct PrecomputedKeyVisitor;
+
+        impl<'de> Visitor<'de> for PrecomputedKeyVisitor {
+            type Value = PrecomputedKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a 32-byte precomputed key")
+            }
+
+            fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
+            where
+                S: SeqAccess<'de>,
+            {
+                // Fill the key in place so the `Drop` impl zeroizes any
+                // partially deserialized key material on error
+                let mut key = PrecomputedKey([0; KEY_SIZE]);
+                for (i, byte) in key.0.iter_mut().enumerate() {
+                    *byte = match seq.next_element()? {
+                        Some(val) => val,
+                        None => {
+                            return Err(Error::invalid_length(i, &self));
+                        }
+                    }
+                }
+                Ok(key)
+            }
+
+            fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                if bytes.len() != KEY_SIZE {
+                    return Err(Error::invalid_length(bytes.len(), &self));
+                }
+
+                let mut key = PrecomputedKey([0; KEY_SIZE]);
+                key.0.copy_from_slice(bytes);
+                Ok(key)
+            }
+
+            fn visit_str<E>(self, encoded: &str) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                // Don't echo the (secret) input in the error
+                base64::decode(encoded.as_bytes())
+                    .map(PrecomputedKey)
+                    .ok_or_else(|| Error::invalid_value(Unexpected::Other("string"), &self))
+            }
+        }
+
+        // Human-readable formats also accept the sequence form which they
+        // used before keys were encoded as Base64 strings
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(PrecomputedKeyVisitor)
+        } else {
+            deserializer.deserialize_bytes(PrecomputedKeyVisitor)
+        }
+    }
+}
+
+/// Emit a telemetry event for the result of an AEAD operation.
+///
+/// Events only include the box type, operation and message length: never
+/// any key, nonce or message bytes. This expands to nothing unless the
+/// `tracing` feature is enabled.
+macro_rules! trace_aead {
+    ($box:ty, $op:literal, $failure:literal, $len:expr, $result:expr) => {
+        #[cfg(feature = "tracing")]
+        match $result {
+            Ok(_) => tracing::trace!(
+                target: "crypto_box",
+                cipher = stringify!($box),
+                operation = $op,
+                len = $len,
+            ),
+            Err(_) => tracing::debug!(
+                target: "crypto_box",
+                cipher = stringify!($box),
+                operation = $op,
+                len = $len,
+                $failure
+            ),
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = ($len, $result);
+    };
+}
+
+macro_rules! impl_aead_in_place {
+    ($box:ty, $nonce_size:ty, $tag_size:ty, $ct_overhead:ty) => {
+        impl AeadCore for $box {
+            type NonceSize = $nonce_size;
+            type TagSize = $tag_size;
+            type CiphertextOverhead = $ct_overhead;
+        }
+
+        impl AeadInPlace for $box {
+            fn encrypt_in_place(
+                &self,
+                nonce: &GenericArray<u8, Self::NonceSize>,
+                associated_data: &[u8],
+                buffer: &mut dyn Buffer,
+            ) -> Result<(), aead::Error> {
+                let len = buffer.len();
+                let result = self.cipher.encrypt_in_place(nonce, associated_data, buffer);
+                trace_aead!($box, "encrypt", "encryption failed", len, &result);
+                result
+            }
+
+            fn encrypt_in_place_detached(
+                &self,
+                nonce: &GenericArray<u8, Self::NonceSize>,
+                associated_data: &[u8],
+                buffer: &mut [u8],
+            ) -> Result<Tag, aead::Error> {
+                let result = self
+                    .cipher
+                    .encrypt_in_place_detached(nonce, associated_data, buffer);
+                trace_aead!($box, "encrypt", "encryption failed", buffer.len(), &result);
+                result
+            }
+
+            fn decrypt_in_place(
+                &self,
+                nonce: &GenericArray<u8, Self::NonceSize>,
+                associated_data: &[u8],
+                buffer: &mut dyn Buffer,
+            ) -> Result<(), aead::Error> {
+                let len = buffer.len();
+                // Reject input too short to hold a tag before handing it to
+                // the cipher, so adversarial lengths are always a clean error
+                let result = if len < TAG_SIZE {
+                    Err(aead::Error)
+                } else {
+                    self.cipher.decrypt_in_place(nonce, associated_data, buffer)
+                };
+                trace_aead!($box, "decrypt", "authentication failed", len, &result);
+                result
+            }
+
+            fn decrypt_in_place_detached(
+                &self,
+                nonce: &GenericArray<u8, Self::NonceSize>,
+                associated_data: &[u8],
+                buffer: &mut [u8],
+                tag: &Tag,
+            ) -> Result<(), aead::Error> {
+                let result =
+                    self.cipher
+                        .decrypt_in_place_detached(nonce, associated_data, buffer, tag);
+                trace_aead!(
+                    $box,
+                    "decrypt",
+                    "authentication failed",
+                    buffer.len(),
+                    &result
+                );
+                result
+            }
+        }
+    };
+}
+
+/// Alias for [`SalsaBox`].
+pub type Box = SalsaBox;
+
+/// Public-key encryption scheme based on the [X25519] Elliptic Curve
+/// Diffie-Hellman function and the [XSalsa20Poly1305] authenticated encryption
+/// cipher.
+///
+/// This type impls the [`aead::Aead`] trait, and otherwise functions as a
+/// symmetric Authenticated Encryption with Associated Data (AEAD) cipher
+/// once instantiated.
+///
+/// Cloning a [`SalsaBox`] copies its derived key: clones share no state, and
+/// each zeroizes its own copy of the key when dropped.
+///
+/// [X25519]: https://cr.yp.to/ecdh.html
+/// [XSalsa20Poly1305]: https://github.com/RustCrypto/AEADs/tree/master/xsalsa20poly1305
+#[derive(Clone)]
+pub struct SalsaBox {
+    cipher: XSalsa20Poly1305,
+    key: PrecomputedKey,
+}
+
+impl SalsaBox {
+    /// Create a new [`SalsaBox`], performing X25519 Diffie-Hellman to derive
+    /// a shared secret from the provided public and secret keys.
+    ///
+    /// The secret key may be any [`DiffieHellman`] implementation, e.g. a
+    /// [`SecretKey`] or a key held in a hardware token.
+    pub fn new<S>(public_key: &PublicKey, secret_key: &S) -> Self
+    where
+        S: DiffieHellman + ?Sized,
+    {
+        SalsaBox::from(&PrecomputedKey::new(public_key, secret_key))
+    }
+
+    /// Create a new [`SalsaBox`] like [`SalsaBox::new`], but clamping the
+    /// secret key's bytes before performing Diffie-Hellman.
+    ///
+    /// X25519 already clamps scalars internally, so this always derives the
+    /// same box as [`SalsaBox::new`], including for hand-crafted scalars
+    /// which are not clamped. It makes the clamping explicit, matching tools
+    /// which store and import pre-clamped secret keys.
+    pub fn new_clamped(public_key: &PublicKey, secret_key: &SecretKey) -> Self {
+        let mut scalar = Zeroizing::new(secret_key.0);
+        clamp(&mut scalar);
+        SalsaBox::new(public_key, &SecretKey(*scalar))
+    }
+
+    /// Re-derive this [`SalsaBox`] in place for new public and secret keys,
+    /// e.g. after a peer rotates their key.
+    ///
+    /// The previous key material is zeroized.
+    pub fn rederive<S>(&mut self, public_key: &PublicKey, secret_key: &S)
+    where
+        S: DiffieHellman + ?Sized,
+    {
+        // Dropping the previous cipher and key zeroizes them
+        *self = SalsaBox::new(public_key, secret_key);
+    }
+
+    /// Split this [`SalsaBox`] into two independent boxes with the same key,
+    /// e.g. for separate sending and receiving tasks.
+    ///
+    /// Both boxes own a copy of the key, so dropping one of them doesn't
+    /// affect the other.
+    pub fn split(self) -> (SalsaBox, SalsaBox) {
+        (self.clone(), self)
+    }
+
+    /// Create a new [`SalsaBox`] from the raw output of an X25519
+    /// Diffie-Hellman computed elsewhere, e.g. inside a secure enclave.
+    ///
+    /// Unlike [`SalsaBox::from_key`], this still applies the HSalsa20
+    /// derivation to the shared secret, exactly like [`SalsaBox::new`] does
+    /// after performing Diffie-Hellman itself.
+    pub fn from_dh_output(shared: &[u8; KEY_SIZE]) -> Self {
+        SalsaBox::from(&PrecomputedKey(hazmat::hsalsa_derive(shared)))
+    }
+
+    /// Create a new [`SalsaBox`] from an [`x25519_dalek::SharedSecret`], e.g.
+    /// one produced by a handshake performed with `x25519-dalek` directly.
+    ///
+    /// This applies the HSalsa20 derivation to the shared secret (see
+    /// [`SalsaBox::from_dh_output`]).
+    #[cfg(feature = "dalek")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dalek")))]
+    pub fn from_dalek_shared(shared: &x25519_dalek::SharedSecret) -> Self {
+        Self::from_dh_output(shared.as_bytes())
+    }
+
+    /// Create a new [`SalsaBox`] from an already derived 32-byte key.
+    ///
+    /// This skips both the X25519 Diffie-Hellman and HSalsa20 steps: the key
+    /// is used as-is as the XSalsa20Poly1305 key. Use
+    /// [`SalsaBox::from_dh_output`] for a raw Diffie-Hellman output instead.
+    ///
+    /// The key can be given as any type which implements `AsRef<[u8; 32]>`,
+    /// such as a wrapper over protected memory, a [`PrecomputedKey`], or a
+    /// [`GenericArray`]. Plain arrays can be passed as
+    /// `GenericArray::from(key)`.
+    ///
+    /// The box keeps two copies of the key, both zeroized when it is dropped:
+    /// one in the XSalsa20Poly1305 cipher, and one used to derive subkeys
+    /// and key identifiers (e.g. [`SalsaBox::key_id`]).
+    pub fn from_key<K>(key: K) -> Self
+    where
+        K: AsRef<[u8; KEY_SIZE]>,
+    {
+        Self::from_precomputed(PrecomputedKey(*key.as_ref()))
+    }
+
+    /// Create a new [`SalsaBox`] which takes ownership of the given key.
+    pub(crate) fn from_precomputed(key: PrecomputedKey) -> Self {
+        SalsaBox {
+            cipher: XSalsa20Poly1305::new(GenericArray::from_slice(&key.0)),
+            key,
+        }
+    }
+
+    /// Generate a random nonce suitable for use with this [`SalsaBox`].
+    ///
+    /// This is a convenience method which forwards to [`generate_nonce`].
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")]
+    /// # {
+    /// use crypto_box::{SalsaBox, SecretKey};
+    ///
+    /// let mut rng = crypto_box::rand_core::OsRng;
+    /// let alice_secret_key = SecretKey::generate(&mut rng);
+    /// let bob_public_key = SecretKey::generate(&mut rng).public_key();
+    /// let alice_box = SalsaBox::new(&bob_public_key, &alice_secret_key);
+    ///
+    /// let nonce = alice_box.generate_nonce(&mut rng);
+    /// # }
+    /// ```
+    pub fn generate_nonce<T>(&self, csprng: &mut T) -> Nonce
+    where
+        T: RngCore + CryptoRng,
+    {
+        generate_nonce(csprng)
+    }
+
+    /// Get the maximum length of the plaintext of a ciphertext of the given
+    /// length, i.e. the length of its plaintext once the tag is removed.
+    ///
+    /// Returns 0 if `buffer_len` is too short to even hold a tag.
+    pub const fn max_plaintext_len(buffer_len: usize) -> usize {
+        buffer_len.saturating_sub(TAG_SIZE)
+    }
+
+    /// Get the length of the ciphertext of a plaintext of the given length,
+    /// i.e. the buffer length required to encrypt it in place.
+    ///
+    /// Returns `None` if the length overflows.
+    pub const fn required_ciphertext_len(plaintext_len: usize) -> Option<usize> {
+        plaintext_len.checked_add(TAG_SIZE)
+    }
+
+    /// Encrypt the given plaintext under a freshly generated random nonce.
+    ///
+    /// Returns the nonce alongside the ciphertext, as the nonce must be
+    /// transmitted to the recipient in order for them to decrypt the message.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt_with_nonce<T>(
+        &self,
+        csprng: &mut T,
+        plaintext: &[u8],
+    ) -> Result<(Nonce, Vec<u8>), aead::Error>
+    where
+        T: RngCore + CryptoRng,
+    {
+        let nonce = generate_nonce(csprng);
+        let ciphertext = self.encrypt(&nonce, plaintext)?;
+        Ok((nonce, ciphertext))
+    }
+
+    /// Decrypt the given ciphertext, returning whether authentication
+    /// succeeded as a [`Choice`] rather than as a [`Result`].
+    ///
+    /// Returns a [`Choice`] which is set if the ciphertext was authentic,
+    /// along with a buffer which contains the plaintext in that case. This
+    /// lets the caller's code avoid branching on the outcome, e.g. to select
+    /// between the plaintext and a fallback in constant time.
+    ///
+    /// # ⚠️ Warning
+    ///
+    /// This function itself is **not** branch-free: the underlying AEAD
+    /// verifies the tag and returns a [`Result`], from which the [`Choice`]
+    /// is built. Whether the ciphertext was authentic may therefore still be
+    /// observable through timing, e.g. because decryption is skipped when
+    /// the tag doesn't match.
+    ///
+    /// The returned buffer is *always* produced. If authentication failed its
+    /// contents are unauthenticated garbage (currently the ciphertext itself)
+    /// which MUST NOT be used for anything other than constant-time selection
+    /// against the returned [`Choice`].
+    ///
+    /// Ciphertexts shorter than a Poly1305 tag are rejected up front, as their
+    /// length is public information.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn decrypt_ct(&self, nonce: &Nonce, ciphertext: &[u8]) -> (Choice, Vec<u8>) {
+        if ciphertext.len() < TAG_SIZE {
+            return (Choice::from(0), Vec::new());
+        }
+
+        let (tag, ciphertext) = ciphertext.split_at(TAG_SIZE);
+        let mut buffer = ciphertext.to_vec();
+        let result = self.decrypt_in_place_detached(nonce, b"", &mut buffer, Tag::from_slice(tag));
+
+        (Choice::from(result.is_ok() as u8), buffer)
+    }
+
+    /// Compute a key confirmation value over the given handshake transcript.
+    ///
+    /// This is a keyed BLAKE2b MAC of the transcript under this box's derived
+    /// key. Both parties to a handshake can compute it and compare the results
+    /// (in constant time) to confirm they derived the same box, without
+    /// revealing the key itself.
+    #[cfg(feature = "blake2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blake2")))]
+    pub fn key_confirmation(&self, transcript: &[u8]) -> [u8; 32] {
+        use blake2::{digest::Mac, Blake2bMac};
+
+        let mut mac = Blake2bMac::<U32>::new_with_salt_and_personal(
+            &self.key.0,
+            b"",
+            KEY_CONFIRMATION_PERSONA,
+        )
+        .expect("key and persona lengths are valid");
+        mac.update(transcript);
+        mac.finalize().into_bytes().into()
+    }
+
+    /// Compute a short identifier for this box's derived key, e.g. to let
+    /// receivers route ciphertexts to the right key during key rotation.
+    ///
+    /// This is a keyed BLAKE2b MAC, truncated to 8 bytes, of an empty message
+    /// under this box's derived key. It doesn't leak the key, and is unique
+    /// enough to tell keys apart, but it is not an authentication mechanism:
+    /// a ciphertext tagged with a key ID must still be decrypted to be
+    /// trusted.
+    #[cfg(feature = "blake2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blake2")))]
+    pub fn key_id(&self) -> [u8; 8] {
+        use blake2::{digest::Mac, Blake2bMac};
+        use xsalsa20poly1305::aead::consts::U8;
+
+        Blake2bMac::<U8>::new_with_salt_and_personal(&self.key.0, b"", KEY_ID_PERSONA)
+            .expect("key and persona lengths are valid")
+            .finalize()
+            .into_bytes()
+            .into()
+    }
+
+    /// Derive a new [`SalsaBox`] for the given key rotation epoch.
+    ///
+    /// The new key is derived with HKDF-SHA256 from this box's key, using
+    /// the big endian epoch number as part of the info string. Boxes for
+    /// different epochs have unrelated keys, so nonces used in one epoch can
+    /// safely be reused in another, without performing a new Diffie-Hellman.
+    #[cfg(feature = "hkdf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hkdf")))]
+    pub fn rekey(&self, epoch: u64) -> SalsaBox {
+        self.derive_subkey(&[REKEY_INFO, &epoch.to_be_bytes()])
+    }
+
+    /// Derive separate encryption and authentication keys from this box's
+    /// key, for protocols which use their own cipher and MAC.
+    ///
+    /// Both keys are derived with HKDF-SHA256 from this box's key, using
+    /// distinct labels followed by `info` as the info strings. They are
+    /// deterministic for a given box and `info`, and independent from each
+    /// other and from the box's own key.
+    #[cfg(feature = "hkdf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hkdf")))]
+    pub fn derive_enc_mac_keys(&self, info: &[u8]) -> (EncKey, MacKey) {
+        let [enc_info, mac_info] = ENC_MAC_INFO;
+        let mut enc_key = EncKey([0u8; KEY_SIZE]);
+        let mut mac_key = MacKey([0u8; KEY_SIZE]);
+        self.expand(&[enc_info, info], &mut enc_key.0);
+        self.expand(&[mac_info, info], &mut mac_key.0);
+        (enc_key, mac_key)
+    }
+
+    /// Derive a new [`SalsaBox`] with HKDF-SHA256 from this box's key, using
+    /// the concatenation of `info` as the info string.
+    #[cfg(feature = "hkdf")]
+    fn derive_subkey(&self, info: &[&[u8]]) -> SalsaBox {
+        let mut key = PrecomputedKey([0u8; KEY_SIZE]);
+        self.expand(info, &mut key.0);
+        SalsaBox::from(&key)
+    }
+
+    /// Expand this box's key into `okm` with HKDF-SHA256, using the
+    /// concatenation of `info` as the info string.
+    #[cfg(feature = "hkdf")]
+    fn expand(&self, info: &[&[u8]], okm: &mut [u8; KEY_SIZE]) {
+        let hkdf = hkdf_crate::Hkdf::<sha2::Sha256>::new(None, &self.key.0);
+        hkdf.expand_multi_info(info, okm)
+            .expect("output length is valid");
+    }
+
+    /// Verify the Poly1305 tag of the given ciphertext without decrypting it.
+    ///
+    /// The tag is compared in constant time. No plaintext is recovered, so
+    /// this can be used by parties which only need to check a message's
+    /// authenticity.
+    pub fn verify(&self, nonce: &Nonce, ciphertext: &[u8]) -> Result<(), aead::Error> {
+        if ciphertext.len() < TAG_SIZE {
+            return Err(aead::Error);
+        }
+
+        let (tag, ciphertext) = ciphertext.split_at(TAG_SIZE);
+
+        // Derive Poly1305 key from the first 32-bytes of the XSalsa20 keystream
+        let mut cipher = XSalsa20::new(GenericArray::from_slice(&self.key.0), nonce);
+        let mut mac_key = poly1305::Key::default();
+        cipher.apply_keystream(&mut mac_key);
+
+        let expected_tag = Poly1305::new(&mac_key)
+            .compute_unpadded(ciphertext)
+            .into_bytes();
+        mac_key.zeroize();
+
+        if expected_tag.ct_eq(Tag::from_slice(tag)).into() {
+            Ok(())
+        } else {
+            Err(aead::Error)
+        }
+    }
+
+    /// Encrypt the given plaintext, rejecting an all-zero nonce.
+    ///
+    /// An all-zero nonce is usually the sign of a nonce which was never
+    /// initialized, and reusing it across messages is catastrophic. Callers
+    /// which deliberately use a fixed nonce (e.g. for deterministic
+    /// encryption of single-use keys) can set `allow_zero_nonce` to bypass
+    /// the check.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt_checked_nonce(
+        &self,
+        nonce: &Nonce,
+        plaintext: &[u8],
+        allow_zero_nonce: bool,
+    ) -> Result<Vec<u8>, Error> {
+        if !allow_zero_nonce && bool::from(nonce.ct_eq(&Nonce::default())) {
+            return Err(Error::ZeroNonce);
+        }
+
+        Ok(self.encrypt(nonce, plaintext)?)
+    }
+
+    /// Decrypt a message from `peer_pk` which was encrypted to one of several
+    /// candidate secret keys, e.g. after a key rotation.
+    ///
+    /// Returns the index of the matching key along with the plaintext, or
+    /// `None` if no key decrypts the message.
+    ///
+    /// Every key is tried, even after a match, so the time taken doesn't
+    /// depend on which key matched. It still depends on the number of keys,
+    /// and isn't strictly constant time, as the plaintext is only recorded
+    /// for the matching key.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn decrypt_with_keys(
+        peer_pk: &PublicKey,
+        secret_keys: &[SecretKey],
+        nonce: &Nonce,
+        ciphertext: &[u8],
+    ) -> Option<(usize, Vec<u8>)> {
+        let mut result = None;
+
+        for (i, secret_key) in secret_keys.iter().enumerate() {
+            if let Ok(plaintext) = SalsaBox::new(peer_pk, secret_key).decrypt(nonce, ciphertext) {
+                result.get_or_insert((i, plaintext));
+            }
+        }
+
+        result
+    }
+
+    /// Encrypt the given plaintext, first rejecting it with
+    /// [`Error::TooLarge`] if it is longer than `max_len` bytes.
+    ///
+    /// The check happens before any allocation or cryptographic work, making
+    /// it a cheap guard against oversized input, e.g. in network services.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt_bounded(
+        &self,
+        nonce: &Nonce,
+        plaintext: &[u8],
+        max_len: usize,
+    ) -> Result<Vec<u8>, Error> {
+        if plaintext.len() > max_len {
+            return Err(Error::TooLarge {
+                max: max_len,
+                got: plaintext.len(),
+            });
+        }
+
+        Ok(self.encrypt(nonce, plaintext)?)
+    }
+
+    /// Decrypt the given ciphertext, distinguishing ciphertexts which are too
+    /// short to contain a tag ([`Error::CiphertextTooShort`]) from ones which
+    /// fail to authenticate ([`Error::Aead`]).
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn try_decrypt(&self, nonce: &Nonce, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        if ciphertext.len() < TAG_SIZE {
+            return Err(Error::CiphertextTooShort {
+                min: TAG_SIZE,
+                got: ciphertext.len(),
+            });
+        }
+
+        Ok(self.decrypt(nonce, ciphertext)?)
+    }
+
+    /// Decrypt the given ciphertext using a nonce given as a slice, e.g.
+    /// parsed from an untrusted message.
+    ///
+    /// Returns [`Error::InvalidNonceLength`] if the nonce isn't 24 bytes long,
+    /// rather than panicking as [`GenericArray::from_slice`] does.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn decrypt_slice_nonce(&self, nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        let expected = Nonce::default().len();
+        if nonce.len() != expected {
+            return Err(Error::InvalidNonceLength {
+                expected,
+                got: nonce.len(),
+            });
+        }
+
+        Ok(self.decrypt(Nonce::from_slice(nonce), ciphertext)?)
+    }
+
+    /// Decrypt the given ciphertext, then zeroize the ciphertext buffer, for
+    /// protocols where the ciphertext itself is sensitive.
+    ///
+    /// The buffer keeps its length, but its contents are zeroed. It is only
+    /// wiped when decryption succeeds: on failure it is left intact, e.g. so
+    /// that it can be logged or retried.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn decrypt_and_wipe(
+        &self,
+        nonce: &Nonce,
+        ciphertext: &mut Vec<u8>,
+    ) -> Result<Vec<u8>, Error> {
+        let plaintext = self.try_decrypt(nonce, ciphertext)?;
+        ciphertext.as_mut_slice().zeroize();
+        Ok(plaintext)
+    }
+
+    /// Encrypt the given plaintext into a [`heapless::Vec`].
+    ///
+    /// The capacity `N` is checked to be large enough for the plaintext and
+    /// tag before any encryption is performed.
+    #[cfg(feature = "heapless")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+    pub fn encrypt_heapless<const N: usize>(
+        &self,
+        nonce: &Nonce,
+        plaintext: &[u8],
+    ) -> Result<heapless::Vec<u8, N>, Error> {
+        let required = plaintext.len().saturating_add(TAG_SIZE);
+        if N < required {
+            return Err(Error::BufferTooSmall {
+                required,
+                capacity: N,
+            });
+        }
+
+        let mut buffer = heapless::Vec::new();
+        buffer
+            .extend_from_slice(plaintext)
+            .expect("capacity checked above");
+        self.encrypt_in_place(nonce, b"", &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Encrypt the given plaintext, returning `tag || ciphertext`.
+    ///
+    /// This is built on the detached primitives, so the tag placement does
+    /// not depend on the layout used by the [`Aead`] impl (which, for
+    /// [`SalsaBox`], happens to be the same one, as in NaCl's
+    /// `crypto_box_easy`).
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt_tag_prefixed(
+        &self,
+        nonce: &Nonce,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        let mut buffer = Vec::with_capacity(TAG_SIZE + plaintext.len());
+        buffer.extend_from_slice(&[0u8; TAG_SIZE]);
+        buffer.extend_from_slice(plaintext);
+
+        let tag = self.encrypt_in_place_detached(nonce, b"", &mut buffer[TAG_SIZE..])?;
+        buffer[..TAG_SIZE].copy_from_slice(&tag);
+        Ok(buffer)
+    }
+
+    /// Decrypt a `tag || ciphertext` message produced by
+    /// [`SalsaBox::encrypt_tag_prefixed`].
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn decrypt_tag_prefixed(
+        &self,
+        nonce: &Nonce,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        if ciphertext.len() < TAG_SIZE {
+            return Err(aead::Error);
+        }
+
+        let (tag, ciphertext) = ciphertext.split_at(TAG_SIZE);
+        let mut buffer = ciphertext.to_vec();
+        self.decrypt_in_place_detached(nonce, b"", &mut buffer, Tag::from_slice(tag))?;
+        Ok(buffer)
+    }
+
+    /// Encrypt the given plaintext, binding it to the given context.
+    ///
+    /// XSalsa20Poly1305 does not support associated data, so instead the
+    /// context is length-prefixed and prepended to the plaintext prior to
+    /// encryption. Unlike true associated data, the context is therefore
+    /// *encrypted* as well as authenticated, and adds to the length of the
+    /// resulting ciphertext.
+    ///
+    /// Use [`SalsaBox::decrypt_with_context`] to decrypt the message.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt_with_context(
+        &self,
+        nonce: &Nonce,
+        context: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        let mut buffer =
+            Vec::with_capacity(CONTEXT_LEN_SIZE + context.len() + plaintext.len() + TAG_SIZE);
+        buffer.extend_from_slice(&(context.len() as u64).to_le_bytes());
+        buffer.extend_from_slice(context);
+        buffer.extend_from_slice(plaintext);
+
+        let result = self.encrypt_in_place(nonce, b"", &mut buffer);
+        if result.is_err() {
+            buffer.zeroize();
+        }
+
+        result.map(|_| buffer)
+    }
+
+    /// Decrypt a message produced by [`SalsaBox::encrypt_with_context`],
+    /// verifying that it was bound to the given context.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn decrypt_with_context(
+        &self,
+        nonce: &Nonce,
+        context: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        let mut buffer = self.decrypt(nonce, ciphertext)?;
+        let prefix_len = CONTEXT_LEN_SIZE + context.len();
+
+        let valid = buffer.len() >= prefix_len && {
+            let mut context_len = [0u8; CONTEXT_LEN_SIZE];
+            context_len.copy_from_slice(&buffer[..CONTEXT_LEN_SIZE]);
+            u64::from_le_bytes(context_len) == context.len() as u64
+                && bool::from(buffer[CONTEXT_LEN_SIZE..prefix_len].ct_eq(context))
+        };
+
+        if !valid {
+            buffer.zeroize();
+            return Err(aead::Error);
+        }
+
+        buffer.drain(..prefix_len);
+        Ok(buffer)
+    }
+
+    /// Lazily decrypt a sequence of length-prefixed records, each made of a
+    /// 32-bit little endian ciphertext length followed by the ciphertext.
+    ///
+    /// Each record is decrypted under the next nonce of `nonces`. A record
+    /// whose length prefix or ciphertext is truncated yields
+    /// [`Error::InvalidLength`], and a record which fails to decrypt yields
+    /// [`Error::Aead`]: in both cases, iteration stops after the error.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn decrypt_records<'a>(
+        &'a self,
+        mut nonces: NonceCounter,
+        data: &'a [u8],
+    ) -> impl Iterator<Item = Result<Vec<u8>, Error>> + 'a {
+        let mut remaining = Some(data);
+
+        core::iter::from_fn(move || {
+            let data = remaining.take().filter(|data| !data.is_empty())?;
+
+            if data.len() < RECORD_LEN_SIZE {
+                return Some(Err(Error::InvalidLength {
+                    expected: RECORD_LEN_SIZE,
+                    got: data.len(),
+                }));
+            }
+
+            let (len, data) = data.split_at(RECORD_LEN_SIZE);
+            let mut len_bytes = [0u8; RECORD_LEN_SIZE];
+            len_bytes.copy_from_slice(len);
+            let len = u32::from_le_bytes(len_bytes) as usize;
+
+            if data.len() < len {
+                return Some(Err(Error::InvalidLength {
+                    expected: len,
+                    got: data.len(),
+                }));
+            }
+
+            let (ciphertext, rest) = data.split_at(len);
+            let result = nonces
+                .next_nonce()
+                .and_then(|nonce| self.decrypt(&nonce, ciphertext))
+                .map_err(Error::from);
+
+            if result.is_ok() {
+                remaining = Some(rest);
+            }
+
+            Some(result)
+        })
+    }
+
+    /// Encrypt a large message as a sequence of chunks of `chunk_size` bytes
+    /// of plaintext, returning a single framed blob.
+    ///
+    /// Each chunk is encrypted under a nonce made of a random 19-byte prefix,
+    /// a 32-bit big endian chunk index and a last-chunk flag, like the
+    /// [STREAM] construction. This prevents chunks from being reordered,
+    /// dropped or truncated without detection. The blob is:
+    ///
+    /// - the 19-byte random nonce prefix
+    /// - zero or more full chunks of `chunk_size` bytes of plaintext (plus
+    ///   tag)
+    /// - a final chunk of strictly less than `chunk_size` bytes of plaintext
+    ///   (plus tag), which may be empty
+    ///
+    /// Use [`SalsaBox::decrypt_large`] with the same `chunk_size` to decrypt
+    /// the blob.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    ///
+    /// [STREAM]: https://eprint.iacr.org/2015/189.pdf
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt_large<T>(
+        &self,
+        csprng: &mut T,
+        plaintext: &[u8],
+        chunk_size: usize,
+    ) -> Result<Vec<u8>, aead::Error>
+    where
+        T: RngCore + CryptoRng,
+    {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+
+        let mut prefix = [0u8; LARGE_NONCE_PREFIX_SIZE];
+        csprng.fill_bytes(&mut prefix);
+
+        let chunk_count = plaintext.len() / chunk_size + 1;
+        let mut blob = Vec::with_capacity(prefix.len() + plaintext.len() + chunk_count * TAG_SIZE);
+        blob.extend_from_slice(&prefix);
+
+        // The final chunk is the remainder, which is empty if the plaintext
+        // is a multiple of the chunk size
+        let full_len = plaintext.len() - plaintext.len() % chunk_size;
+        let final_chunk = &plaintext[full_len..];
+        let chunks = plaintext[..full_len]
+            .chunks(chunk_size)
+            .map(|chunk| (chunk, false))
+            .chain(core::iter::once((final_chunk, true)));
+
+        for (index, (chunk, last)) in chunks.enumerate() {
+            let nonce = large_nonce(&prefix, index, last)?;
+            let start = blob.len() + TAG_SIZE;
+            blob.extend_from_slice(&[0u8; TAG_SIZE]);
+            blob.extend_from_slice(chunk);
+
+            let tag = self.encrypt_in_place_detached(&nonce, b"", &mut blob[start..])?;
+            blob[start - TAG_SIZE..start].copy_from_slice(&tag);
+        }
+
+        Ok(blob)
+    }
+
+    /// Decrypt a blob produced by [`SalsaBox::encrypt_large`].
+    ///
+    /// The `chunk_size` must match the one used to encrypt the blob.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn decrypt_large(&self, blob: &[u8], chunk_size: usize) -> Result<Vec<u8>, aead::Error> {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+
+        if blob.len() < LARGE_NONCE_PREFIX_SIZE {
+            return Err(aead::Error);
+        }
+
+        let (prefix, mut frames) = blob.split_at(LARGE_NONCE_PREFIX_SIZE);
+        let frame_size = chunk_size + TAG_SIZE;
+        let mut plaintext = Vec::with_capacity(frames.len());
+        let mut index = 0;
+
+        let result = loop {
+            // A frame which is shorter than a full frame is the final one
+            let last = frames.len() < frame_size;
+            let (frame, rest) = frames.split_at(if last { frames.len() } else { frame_size });
+            frames = rest;
+
+            if frame.len() < TAG_SIZE {
+                break Err(aead::Error);
+            }
+
+            let (tag, ciphertext) = frame.split_at(TAG_SIZE);
+            let start = plaintext.len();
+            plaintext.extend_from_slice(ciphertext);
+
+            let result = large_nonce(prefix, index, last).and_then(|nonce| {
+                self.decrypt_in_place_detached(
+                    &nonce,
+                    b"",
+                    &mut plaintext[start..],
+                    Tag::from_slice(tag),
+                )
+            });
+
+            if result.is_err() || last {
+                break result;
+            }
+
+            index += 1;
+        };
+
+        if result.is_err() {
+            plaintext.zeroize();
+            return Err(aead::Error);
+        }
+
+        Ok(plaintext)
+    }
+}
+
+/// Compute the nonce of a chunk of [`SalsaBox::encrypt_large`].
+#[cfg(feature = "alloc")]
+fn large_nonce(prefix: &[u8], index: usize, last: bool) -> Result<Nonce, aead::Error> {
+    use core::convert::TryFrom;
+
+    let index = u32::try_from(index).map_err(|_| aead::Error)?;
+    let mut nonce = Nonce::default();
+    nonce[..LARGE_NONCE_PREFIX_SIZE].copy_from_slice(prefix);
+    nonce[LARGE_NONCE_PREFIX_SIZE..LARGE_NONCE_PREFIX_SIZE + 4]
+        .copy_from_slice(&index.to_be_bytes());
+    nonce[LARGE_NONCE_PREFIX_SIZE + 4] = last as u8;
+    Ok(nonce)
+}
+
+impl From<&PrecomputedKey> for SalsaBox {
+    fn from(precomputed_key: &PrecomputedKey) -> SalsaBox {
+        SalsaBox::from_precomputed(precomputed_key.clone())
+    }
+}
+
+impl_aead_in_place!(SalsaBox, U24, U16, U0);
+
+/// Instantiate a [`SalsaBox`] directly from a derived key, for use in generic
+/// code. This bypasses the Diffie-Hellman step: see [`SalsaBox::from_key`].
+impl NewAead for SalsaBox {
+    type KeySize = U32;
+
+    fn new(key: &GenericArray<u8, U32>) -> Self {
+        let mut precomputed_key = PrecomputedKey([0u8; KEY_SIZE]);
+        precomputed_key.0.copy_from_slice(key);
+        SalsaBox::from_precomputed(precomputed_key)
+    }
+}
+
+/// Public-key encryption scheme based on the [X25519] Elliptic Curve
+/// Diffie-Hellman function and the [XChaCha20Poly1305] authenticated encryption
+/// cipher.
+///
+/// This type impls the [`aead::Aead`] trait, and otherwise functions as a
+/// symmetric Authenticated Encryption with Associated Data (AEAD) cipher
+/// once instantiated.
+///
+/// [X25519]: https://cr.yp.to/ecdh.html
+/// [XChaCha20Poly1305]: https://github.com/RustCrypto/AEADs/blob/master/chacha20poly1305/
+#[derive(Clone)]
+pub struct ChaChaBox {
+    cipher: XChaCha20Poly1305,
+    key: Zeroizing<[u8; KEY_SIZE]>,
+}
+
+impl ChaChaBox {
+    /// Create a new [`ChaChaBox`], performing X25519 Diffie-Hellman to derive
+    /// a shared secret from the provided public and secret keys.
+    ///
+    /// The secret key may be any [`DiffieHellman`] implementation, e.g. a
+    /// [`SecretKey`] or a key held in a hardware token.
+    pub fn new<S>(public_key: &PublicKey, secret_key: &S) -> Self
+    where
+        S: DiffieHellman + ?Sized,
+    {
+        let shared_secret = Zeroizing::new(secret_key.diffie_hellman(public_key));
+
+        // Use HChaCha20 to create a uniformly random key from the shared secret
+        let key = Zeroizing::new(hazmat::hchacha_derive(&shared_secret));
+
+        ChaChaBox::from_key(GenericArray::from_slice(&key[..]))
+    }
+
+    /// Create a new [`ChaChaBox`] from an already derived 32-byte key.
+    ///
+    /// This skips both the X25519 Diffie-Hellman and HChaCha20 steps: the key
+    /// is used as-is as the XChaCha20Poly1305 key.
+    ///
+    /// As with [`SalsaBox::from_key`], the key can be given as any type which
+    /// implements `AsRef<[u8; 32]>`, and the box keeps two copies of it, both
+    /// zeroized when it is dropped: one in the XChaCha20Poly1305 cipher, and
+    /// one used to derive key commitments and synthetic nonces.
+    pub fn from_key<K>(key: K) -> Self
+    where
+        K: AsRef<[u8; KEY_SIZE]>,
+    {
+        let key = key.as_ref();
+        ChaChaBox {
+            cipher: XChaCha20Poly1305::new(GenericArray::from_slice(key)),
+            key: Zeroizing::new(*key),
+        }
+    }
+
+    /// Generate a random nonce suitable for use with this [`ChaChaBox`].
+    ///
+    /// This is a convenience method which forwards to [`generate_nonce`].
+    pub fn generate_nonce<T>(&self, csprng: &mut T) -> Nonce
+    where
+        T: RngCore + CryptoRng,
+    {
+        generate_nonce(csprng)
+    }
+
+    /// Encrypt each of the given chunks with the same associated data, under
+    /// sequential nonces starting at `base_nonce` (see [`NonceCounter`]).
+    ///
+    /// Returns the ciphertexts, in order, along with the number of chunks
+    /// (and so of nonces) used. The next unused nonce is `base_nonce`
+    /// incremented by that count.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt_chunks<I, T>(
+        &self,
+        base_nonce: &Nonce,
+        aad: &[u8],
+        chunks: I,
+    ) -> Result<(Vec<Vec<u8>>, usize), aead::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        let mut nonces = NonceCounter::new(*base_nonce);
+        let ciphertexts = chunks
+            .into_iter()
+            .map(|chunk| {
+                let payload = Payload {
+                    msg: chunk.as_ref(),
+                    aad,
+                };
+                self.encrypt(&nonces.next_nonce()?, payload)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let count = ciphertexts.len();
+        Ok((ciphertexts, count))
+    }
+
+    /// Encrypt the given plaintext, binding it to the given message type.
+    ///
+    /// The type is authenticated as length-prefixed associated data, so that
+    /// a message of one type can't be replayed as a message of another type
+    /// (or of a type sharing its prefix). The output is identical to
+    /// [`Aead::encrypt`] with that associated data.
+    ///
+    /// Use [`ChaChaBox::decrypt_typed`] to decrypt the message.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt_typed(
+        &self,
+        nonce: &Nonce,
+        msg_type: &str,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        let type_len = (msg_type.len() as u64).to_le_bytes();
+        let mut buffer = Vec::with_capacity(plaintext.len() + TAG_SIZE);
+        buffer.extend_from_slice(plaintext);
+
+        let tag = self.encrypt_in_place_detached_with_aad_chunks(
+            nonce,
+            &[&type_len, msg_type.as_bytes()],
+            &mut buffer,
+        )?;
+        buffer.extend_from_slice(&tag);
+        Ok(buffer)
+    }
+
+    /// Decrypt a message produced by [`ChaChaBox::encrypt_typed`], verifying
+    /// that it was bound to the given message type.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn decrypt_typed(
+        &self,
+        nonce: &Nonce,
+        msg_type: &str,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        if ciphertext.len() < TAG_SIZE {
+            return Err(aead::Error);
+        }
+
+        let type_len = (msg_type.len() as u64).to_le_bytes();
+        let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - TAG_SIZE);
+        let mut buffer = ciphertext.to_vec();
+
+        self.decrypt_in_place_detached_with_aad_chunks(
+            nonce,
+            &[&type_len, msg_type.as_bytes()],
+            &mut buffer,
+            Tag::from_slice(tag),
+        )?;
+        Ok(buffer)
+    }
+
+    /// Encrypt the given plaintext, also authenticating the nonce itself as
+    /// the associated data.
+    ///
+    /// XChaCha20Poly1305 already fails to authenticate a message under any
+    /// nonce other than the one used to encrypt it, since the Poly1305 key is
+    /// derived from the nonce. Binding the nonce explicitly makes this
+    /// property independent of the cipher, e.g. for protocols which transmit
+    /// the nonce separately from the ciphertext and want tampering with it to
+    /// be rejected by construction rather than decrypting a different
+    /// keystream position.
+    ///
+    /// Use [`ChaChaBox::decrypt_binding_nonce`] to decrypt the message.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt_binding_nonce(
+        &self,
+        nonce: &Nonce,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        let payload = Payload {
+            msg: plaintext,
+            aad: nonce,
+        };
+        self.encrypt(nonce, payload)
+    }
+
+    /// Decrypt a message produced by [`ChaChaBox::encrypt_binding_nonce`].
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn decrypt_binding_nonce(
+        &self,
+        nonce: &Nonce,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        let payload = Payload {
+            msg: ciphertext,
+            aad: nonce,
+        };
+        self.decrypt(nonce, payload)
+    }
+
+    /// Encrypt the given plaintext along with the given timestamp (e.g. in
+    /// seconds since the Unix epoch), so that it can be rejected once stale.
+    ///
+    /// The output is the 8-byte little endian timestamp followed by the
+    /// ciphertext, which authenticates the timestamp as associated data.
+    ///
+    /// Use [`ChaChaBox::decrypt_with_expiry`] to decrypt the message.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn encrypt_with_expiry(
+        &self,
+        nonce: &Nonce,
+        unix_time: u64,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        let timestamp = unix_time.to_le_bytes();
+        let mut buffer = Vec::with_capacity(timestamp.len() + plaintext.len() + TAG_SIZE);
+        buffer.extend_from_slice(&timestamp);
+        buffer.extend_from_slice(plaintext);
+
+        let tag =
+            self.encrypt_in_place_detached(nonce, &timestamp, &mut buffer[timestamp.len()..])?;
+        buffer.extend_from_slice(&tag);
+        Ok(buffer)
+    }
+
+    /// Decrypt a message produced by [`ChaChaBox::encrypt_with_expiry`],
+    /// rejecting it if its timestamp is more than `max_age` older than `now`.
+    ///
+    /// The timestamp is authenticated before its age is checked: messages
+    /// which fail to authenticate return [`Error::Aead`], and stale ones
+    /// return [`Error::Expired`]. Messages timestamped in the future (e.g.
+    /// because of clock skew) are accepted.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn decrypt_with_expiry(
+        &self,
+        nonce: &Nonce,
+        ciphertext: &[u8],
+        now: u64,
+        max_age: u64,
+    ) -> Result<Vec<u8>, Error> {
+        const TIMESTAMP_SIZE: usize = 8;
+
+        if ciphertext.len() < TIMESTAMP_SIZE + TAG_SIZE {
+            return Err(Error::Aead);
+        }
+
+        let (timestamp, ciphertext) = ciphertext.split_at(TIMESTAMP_SIZE);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: timestamp,
+        };
+        let mut plaintext = self.decrypt(nonce, payload)?;
+
+        let mut unix_time = [0u8; TIMESTAMP_SIZE];
+        unix_time.copy_from_slice(timestamp);
+        if now.saturating_sub(u64::from_le_bytes(unix_time)) > max_age {
+            plaintext.zeroize();
+            return Err(Error::Expired);
+        }
+
+        Ok(plaintext)
+    }
+
+    /// Encrypt the given plaintext as a key-committing AEAD.
+    ///
+    /// Poly1305-based AEADs are not key-committing: it is possible to craft
+    /// a ciphertext which decrypts successfully under two different keys,
+    /// which enables partitioning oracle and similar multi-key attacks. This
+    /// prepends a 32-byte commitment to a subkey of the key and to the nonce
+    /// (computed with BLAKE2b-256) to the ciphertext, so that it can only be
+    /// opened with the key it was encrypted with. The output is therefore 48
+    /// bytes longer than the plaintext.
+    ///
+    /// Use [`ChaChaBox::decrypt_committing`] to decrypt the message.
+    #[cfg(all(feature = "alloc", feature = "blake2"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "alloc", feature = "blake2"))))]
+    pub fn encrypt_committing(
+        &self,
+        nonce: &Nonce,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        let commitment = self.commitment(nonce);
+        let mut buffer = Vec::with_capacity(commitment.len() + plaintext.len() + TAG_SIZE);
+        buffer.extend_from_slice(&commitment);
+        buffer.extend_from_slice(plaintext);
+
+        let tag = self.encrypt_in_place_detached(nonce, b"", &mut buffer[commitment.len()..])?;
+        buffer.extend_from_slice(&tag);
+        Ok(buffer)
+    }
+
+    /// Decrypt a message produced by [`ChaChaBox::encrypt_committing`],
+    /// verifying that it was encrypted with this box's key.
+    #[cfg(all(feature = "alloc", feature = "blake2"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "alloc", feature = "blake2"))))]
+    pub fn decrypt_committing(
+        &self,
+        nonce: &Nonce,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        if ciphertext.len() < COMMITMENT_SIZE + TAG_SIZE {
+            return Err(aead::Error);
+        }
+
+        let (commitment, ciphertext) = ciphertext.split_at(COMMITMENT_SIZE);
+        if !bool::from(self.commitment(nonce).ct_eq(commitment)) {
+            return Err(aead::Error);
+        }
+
+        self.decrypt(nonce, ciphertext)
+    }
+
+    /// Encrypt the given plaintext with a synthetic nonce, for nonce-misuse
+    /// resistance.
+    ///
+    /// The nonce is derived from the associated data and the plaintext with
+    /// a PRF (keyed BLAKE2b) under a subkey of this box's key, and prepended
+    /// to the ciphertext. As there is no caller-supplied nonce, there is no nonce
+    /// reuse to get wrong.
+    ///
+    /// However, encryption is deterministic: encrypting the same plaintext
+    /// with the same associated data always produces the same ciphertext,
+    /// which leaks whether two messages are equal. The output is 40 bytes
+    /// longer than the plaintext.
+    ///
+    /// Use [`ChaChaBox::decrypt_siv`] to decrypt the message.
+    #[cfg(all(feature = "alloc", feature = "blake2"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "alloc", feature = "blake2"))))]
+    pub fn encrypt_siv(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, aead::Error> {
+        let nonce = self.synthetic_nonce(aad, plaintext);
+        let mut buffer = Vec::with_capacity(nonce.len() + plaintext.len() + TAG_SIZE);
+        buffer.extend_from_slice(&nonce);
+        buffer.extend_from_slice(plaintext);
+
+        let tag = self.encrypt_in_place_detached(&nonce, aad, &mut buffer[nonce.len()..])?;
+        buffer.extend_from_slice(&tag);
+        Ok(buffer)
+    }
+
+    /// Decrypt a message produced by [`ChaChaBox::encrypt_siv`], verifying
+    /// both its tag and its synthetic nonce.
+    #[cfg(all(feature = "alloc", feature = "blake2"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "alloc", feature = "blake2"))))]
+    pub fn decrypt_siv(&self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, aead::Error> {
+        let nonce_size = Nonce::default().len();
+        if ciphertext.len() < nonce_size + TAG_SIZE {
+            return Err(aead::Error);
+        }
+
+        let (nonce, ciphertext) = ciphertext.split_at(nonce_size);
+        let nonce = Nonce::from_slice(nonce);
+        let payload = Payload {
+            msg: ciphertext,
+            aad,
+        };
+        let mut plaintext = self.decrypt(nonce, payload)?;
+
+        if !bool::from(self.synthetic_nonce(aad, &plaintext).ct_eq(nonce)) {
+            plaintext.zeroize();
+            return Err(aead::Error);
+        }
+
+        Ok(plaintext)
+    }
+
+    /// Derive the synthetic nonce of [`ChaChaBox::encrypt_siv`].
+    #[cfg(all(feature = "alloc", feature = "blake2"))]
+    fn synthetic_nonce(&self, aad: &[u8], plaintext: &[u8]) -> Nonce {
+        use blake2::{digest::Mac, Blake2bMac};
+
+        let key = self.subkey(SIV_KEY_PERSONA);
+        let mut mac = Blake2bMac::<U24>::new_with_salt_and_personal(&key[..], b"", SIV_PERSONA)
+            .expect("key and persona lengths are valid");
+        mac.update(&(aad.len() as u64).to_le_bytes());
+        mac.update(aad);
+        mac.update(plaintext);
+        mac.finalize().into_bytes()
+    }
+
+    /// Compute the commitment to this box's key and the given nonce.
+    #[cfg(all(feature = "alloc", feature = "blake2"))]
+    fn commitment(&self, nonce: &Nonce) -> [u8; COMMITMENT_SIZE] {
+        use blake2::{digest::Digest, Blake2b};
+
+        let key = self.subkey(COMMITMENT_KEY_PERSONA);
+        let mut hasher = Blake2b::<U32>::new();
+        hasher.update(COMMITMENT_LABEL);
+        hasher.update(&key[..]);
+        hasher.update(nonce);
+        hasher.finalize().into()
+    }
+
+    /// Derive a subkey of this box's key for the given purpose, so that the
+    /// cipher's key is never used directly as a BLAKE2b key or input.
+    ///
+    /// This is BLAKE2b-256 of an empty message, keyed with this box's key and
+    /// personalized with the given `persona`.
+    #[cfg(all(feature = "alloc", feature = "blake2"))]
+    fn subkey(&self, persona: &[u8; 16]) -> Zeroizing<[u8; KEY_SIZE]> {
+        use blake2::{digest::Mac, Blake2bMac};
+
+        let mac = Blake2bMac::<U32>::new_with_salt_and_personal(&self.key[..], b"", persona)
+            .expect("key and persona lengths are valid");
+        Zeroizing::new(mac.finalize().into_bytes().into())
     }
 }
 
 impl_aead_in_place!(ChaChaBox, U24, U16, U0);
 
+/// Instantiate a [`ChaChaBox`] directly from a derived key, for use in generic
+/// code. This bypasses the Diffie-Hellman step: see [`ChaChaBox::from_key`].
+impl NewAead for ChaChaBox {
+    type KeySize = U32;
+
+    fn new(key: &GenericArray<u8, U32>) -> Self {
+        ChaChaBox::from_key(key)
+    }
+}
+
+/// Public-key encryption scheme based on the [X25519] Elliptic Curve
+/// Diffie-Hellman function and the IETF [ChaCha20Poly1305] authenticated
+/// encryption cipher, with a 96-bit nonce.
+///
+/// The key is derived the same way as for [`ChaChaBox`], so both boxes share
+/// the same key for a given keypair; this variant exists to interoperate with
+/// peers which are fixed on 96-bit nonces.
+///
+/// # Nonces
+///
+/// A 96-bit nonce is too small to be safely chosen at random: with random
+/// nonces, the probability of a collision reaches 2^-32 after about 2^32
+/// messages. Nonces MUST never be reused with the same key, and should
+/// instead be derived from a message counter (see [`NonceCounter`] for the
+/// extended nonce equivalent). Prefer [`ChaChaBox`] where possible.
+///
+/// [X25519]: https://cr.yp.to/ecdh.html
+/// [ChaCha20Poly1305]: https://github.com/RustCrypto/AEADs/blob/master/chacha20poly1305/
+#[derive(Clone)]
+pub struct IetfChaChaBox {
+    cipher: ChaCha20Poly1305,
+}
+
+impl IetfChaChaBox {
+    /// Create a new [`IetfChaChaBox`], performing X25519 Diffie-Hellman to
+    /// derive a shared secret from the provided public and secret keys.
+    ///
+    /// The secret key may be any [`DiffieHellman`] implementation, e.g. a
+    /// [`SecretKey`] or a key held in a hardware token.
+    pub fn new<S>(public_key: &PublicKey, secret_key: &S) -> Self
+    where
+        S: DiffieHellman + ?Sized,
+    {
+        let shared_secret = Zeroizing::new(secret_key.diffie_hellman(public_key));
+
+        // Use HChaCha20 to create a uniformly random key from the shared secret
+        let key = Zeroizing::new(hazmat::hchacha_derive(&shared_secret));
+
+        IetfChaChaBox {
+            cipher: ChaCha20Poly1305::new(GenericArray::from_slice(&*key)),
+        }
+    }
+}
+
+impl_aead_in_place!(IetfChaChaBox, U12, U16, U0);
+
 #[cfg(test)]
 mod tests {
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_public_key_short_sequence() {
+        use super::PublicKey;
+
+        // Empty and truncated msgpack arrays
+        for invalid in &[&[0x90][..], &[0x92, 0x00, 0x01][..]] {
+            assert!(rmp_serde::from_slice::<PublicKey>(invalid).is_err());
+        }
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     fn test_public_key_serialization() {
@@ -523,4 +2550,115 @@ mod tests {
             "Deserialized public key does not match original"
         );
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_public_key_human_readable_serialization() {
+        use super::PublicKey;
+
+        let mut public_key_bytes = [0; 32];
+        for (i, byte) in public_key_bytes.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let public_key = PublicKey::from(public_key_bytes);
+
+        // Base64 string with serde_json
+        let serialized = serde_json::to_string(&public_key).unwrap();
+        assert_eq!(
+            serialized,
+            "\"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=\""
+        );
+        let deserialized: PublicKey = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, public_key);
+
+        // Bytes with bincode: a length prefix followed by the raw key
+        let serialized = bincode::serialize(&public_key).unwrap();
+        assert_eq!(&serialized[8..], &public_key_bytes);
+        let deserialized: PublicKey = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized, public_key);
+
+        // The sequence form previously emitted by serde_json is still accepted
+        let legacy = serde_json::to_string(&public_key_bytes).unwrap();
+        let deserialized: PublicKey = serde_json::from_str(&legacy).unwrap();
+        assert_eq!(deserialized, public_key);
+
+        // Truncated, non-canonical and invalid encodings are rejected
+        for invalid in &[
+            "[]",
+            "[0, 1]",
+            "\"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh==\"",
+            "\"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh9=\"",
+            "\"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8\"",
+            "\"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwd*h8=\"",
+        ] {
+            assert!(serde_json::from_str::<PublicKey>(invalid).is_err());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_precomputed_key_serialization() {
+        use super::{PrecomputedKey, SalsaBox, SecretKey};
+        use xsalsa20poly1305::aead::Aead;
+
+        let mut rng = rand::thread_rng();
+        let alice_secret_key = SecretKey::generate(&mut rng);
+        let bob_public_key = SecretKey::generate(&mut rng).public_key();
+        let precomputed_key = PrecomputedKey::new(&bob_public_key, &alice_secret_key);
+
+        let nonce = crate::generate_nonce(&mut rng);
+        let ciphertext = SalsaBox::from(&precomputed_key)
+            .encrypt(&nonce, &b"session message"[..])
+            .unwrap();
+
+        // Round-trip serialize with bincode
+        let serialized =
+            bincode::serialize(&precomputed_key).expect("Precomputed key could not be serialized");
+        let deserialized: PrecomputedKey =
+            bincode::deserialize(&serialized).expect("Precomputed key could not be deserialized");
+        assert_eq!(deserialized.as_bytes(), precomputed_key.as_bytes());
+
+        let plaintext = SalsaBox::from(&deserialized)
+            .decrypt(&nonce, &ciphertext[..])
+            .unwrap();
+        assert_eq!(&plaintext[..], b"session message");
+
+        // Round-trip serialize with serde_json
+        let serialized = serde_json::to_string(&precomputed_key)
+            .expect("Precomputed key could not be serialized");
+        assert!(serialized.starts_with('"'));
+        let deserialized: PrecomputedKey =
+            serde_json::from_str(&serialized).expect("Precomputed key could not be deserialized");
+        assert_eq!(deserialized.as_bytes(), precomputed_key.as_bytes());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_postcard_serialization() {
+        use super::{PrecomputedKey, PublicKey, SecretKey};
+
+        let mut public_key_bytes = [0; 32];
+        for (i, byte) in public_key_bytes.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let public_key = PublicKey::from(public_key_bytes);
+
+        // Serialized into a fixed-size buffer, without allocating: a varint
+        // length prefix followed by the raw key
+        let mut buffer = [0u8; 64];
+        let serialized = postcard::to_slice(&public_key, &mut buffer).unwrap();
+        assert_eq!(serialized[0], 32);
+        assert_eq!(&serialized[1..], &public_key_bytes);
+        let deserialized: PublicKey = postcard::from_bytes(serialized).unwrap();
+        assert_eq!(deserialized, public_key);
+
+        let precomputed_key = PrecomputedKey::new(&public_key, &SecretKey::from([1; 32]));
+        let mut buffer = [0u8; 33];
+        let serialized = postcard::to_slice(&precomputed_key, &mut buffer).unwrap();
+        let deserialized: PrecomputedKey = postcard::from_bytes(serialized).unwrap();
+        assert_eq!(deserialized.as_bytes(), precomputed_key.as_bytes());
+
+        // Truncated input is rejected
+        assert!(postcard::from_bytes::<PublicKey>(&serialized[..32]).is_err());
+    }
 }
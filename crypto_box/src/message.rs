@@ -0,0 +1,130 @@
+//! Self-contained encrypted messages.
+
+use crate::{Nonce, SalsaBox};
+use alloc::vec::Vec;
+use rand_core::{CryptoRng, RngCore};
+use xsalsa20poly1305::aead::{Aead, Error};
+
+#[cfg(feature = "serde")]
+use {
+    core::fmt,
+    serde_crate::{
+        de::{Deserialize, Deserializer},
+        ser::{Serialize, Serializer},
+    },
+};
+
+/// Size of the nonce of an [`EncryptedMessage`], in bytes.
+const NONCE_SIZE: usize = 24;
+
+/// Ciphertext bundled with the nonce it was encrypted under.
+///
+/// When the `serde` feature is enabled, this is serialized as a single byte
+/// string containing `nonce || ciphertext`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EncryptedMessage {
+    /// Nonce the message was encrypted under.
+    pub nonce: [u8; NONCE_SIZE],
+
+    /// Ciphertext, including the authentication tag.
+    pub ciphertext: Vec<u8>,
+}
+
+impl SalsaBox {
+    /// Encrypt the given plaintext under a freshly generated random nonce,
+    /// returning an [`EncryptedMessage`] which bundles the two together.
+    pub fn encrypt_message<T>(
+        &self,
+        csprng: &mut T,
+        plaintext: &[u8],
+    ) -> Result<EncryptedMessage, Error>
+    where
+        T: RngCore + CryptoRng,
+    {
+        let mut nonce = [0u8; NONCE_SIZE];
+        csprng.fill_bytes(&mut nonce);
+        let ciphertext = self.encrypt(&Nonce::from(nonce), plaintext)?;
+        Ok(EncryptedMessage { nonce, ciphertext })
+    }
+
+    /// Decrypt an [`EncryptedMessage`] produced by
+    /// [`SalsaBox::encrypt_message`].
+    pub fn decrypt_message(&self, message: &EncryptedMessage) -> Result<Vec<u8>, Error> {
+        self.decrypt(&Nonce::from(message.nonce), message.ciphertext.as_slice())
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for EncryptedMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut bytes = Vec::with_capacity(NONCE_SIZE + self.ciphertext.len());
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&self.ciphertext);
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for EncryptedMessage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde_crate::de::{Error, SeqAccess, Visitor};
+
+        struct EncryptedMessageVisitor;
+
+        impl EncryptedMessageVisitor {
+            fn split<E>(&self, bytes: &[u8]) -> Result<EncryptedMessage, E>
+            where
+                E: Error,
+            {
+                if bytes.len() < NONCE_SIZE {
+                    return Err(Error::invalid_length(bytes.len(), self));
+                }
+
+                let mut nonce = [0u8; NONCE_SIZE];
+                nonce.copy_from_slice(&bytes[..NONCE_SIZE]);
+                Ok(EncryptedMessage {
+                    nonce,
+                    ciphertext: bytes[NONCE_SIZE..].to_vec(),
+                })
+            }
+        }
+
+        impl<'de> Visitor<'de> for EncryptedMessageVisitor {
+            type Value = EncryptedMessage;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a 24-byte nonce followed by a ciphertext")
+            }
+
+            fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
+            where
+                S: SeqAccess<'de>,
+            {
+                // The size hint comes from the input, so don't trust it for
+                // more than a modest preallocation
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                self.split(&bytes)
+            }
+
+            fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                self.split(bytes)
+            }
+        }
+
+        deserializer.deserialize_bytes(EncryptedMessageVisitor)
+    }
+}
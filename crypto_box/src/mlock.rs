@@ -0,0 +1,108 @@
+//! Secret keys locked into RAM.
+//!
+//! A [`SecretKey`] is a plain value which is copied whenever it is moved, so
+//! its memory can't be locked in place. A [`LockedSecretKey`] instead keeps
+//! its key on the heap, at a stable address which is locked into RAM (using
+//! `mlock` or `VirtualLock`) so that it isn't swapped to disk.
+//!
+//! Locking is best-effort: it may fail, e.g. because the process exceeds its
+//! locked memory limit (`RLIMIT_MEMLOCK` on Linux), or because the platform
+//! doesn't support it. Failures are not fatal: the key is still usable, and
+//! [`LockedSecretKey::is_locked`] reports whether locking succeeded. A
+//! warning is also emitted if the `tracing` feature is enabled.
+//!
+//! Memory is locked and unlocked with page granularity, and locks aren't
+//! reference counted by the operating system: dropping a [`LockedSecretKey`]
+//! also unlocks any other data sharing its memory pages. Locked memory may
+//! still be written to disk when the system hibernates.
+
+use crate::{DiffieHellman, PublicKey, SecretKey, KEY_SIZE};
+use alloc::boxed::Box;
+use core::fmt::{self, Debug};
+use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroize;
+
+/// A [`SecretKey`] whose memory is locked into RAM while it is alive.
+///
+/// The key is zeroized before its memory is unlocked when it is dropped.
+pub struct LockedSecretKey {
+    key: Box<SecretKey>,
+    locked: bool,
+}
+
+impl LockedSecretKey {
+    /// Generate a new random [`LockedSecretKey`].
+    ///
+    /// The memory is locked before the key is generated into it, so the key
+    /// bytes are never stored anywhere else.
+    pub fn generate<T>(csprng: &mut T) -> Self
+    where
+        T: RngCore + CryptoRng,
+    {
+        let mut locked_key = Self::lock(Box::new(SecretKey([0u8; KEY_SIZE])));
+        csprng.fill_bytes(&mut locked_key.key.0);
+        locked_key
+    }
+
+    /// Check whether the memory of this key was successfully locked.
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Get the locked [`SecretKey`].
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.key
+    }
+
+    /// Lock the memory of the given key.
+    fn lock(key: Box<SecretKey>) -> Self {
+        let result = region::lock(&*key, KEY_SIZE);
+
+        #[cfg(feature = "tracing")]
+        if let Err(err) = &result {
+            tracing::warn!(target: "crypto_box", "failed to lock secret key memory: {}", err);
+        }
+
+        // The memory is unlocked manually on drop, after zeroizing the key
+        let locked = result.map(core::mem::forget).is_ok();
+        LockedSecretKey { key, locked }
+    }
+}
+
+/// Move a [`SecretKey`] into locked memory.
+///
+/// The key is zeroized at its original location, but copies made by earlier
+/// moves of the key may remain in memory.
+impl From<SecretKey> for LockedSecretKey {
+    fn from(mut secret_key: SecretKey) -> LockedSecretKey {
+        let mut locked_key = Self::lock(Box::new(SecretKey([0u8; KEY_SIZE])));
+        locked_key.key.0.copy_from_slice(&secret_key.0);
+        secret_key.zeroize();
+        locked_key
+    }
+}
+
+impl DiffieHellman for LockedSecretKey {
+    fn diffie_hellman(&self, public_key: &PublicKey) -> [u8; KEY_SIZE] {
+        self.key.diffie_hellman(public_key)
+    }
+}
+
+impl Debug for LockedSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LockedSecretKey")
+            .field("key", &self.key)
+            .field("locked", &self.locked)
+            .finish()
+    }
+}
+
+impl Drop for LockedSecretKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+
+        if self.locked {
+            let _ = region::unlock(&*self.key, KEY_SIZE);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Nonce helpers.
+
+use crate::Nonce;
+use xsalsa20poly1305::aead::Error;
+
+/// Byte order in which a [`NonceCounter`] interprets its nonce.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Endian {
+    /// Most significant byte first: the last byte is incremented first.
+    Big,
+
+    /// Least significant byte first: the first byte is incremented first.
+    ///
+    /// This is libsodium's convention, as used by `sodium_increment`.
+    Little,
+}
+
+/// Counter which produces a sequence of unique nonces.
+///
+/// The nonce is interpreted as an integer which is incremented after each
+/// use, in little-endian byte order by default (matching libsodium's
+/// `sodium_increment`), or in the byte order given to
+/// [`NonceCounter::with_endian`]. Both ends of a channel must use the same
+/// byte order: otherwise their nonces diverge after the first message, which
+/// shows up as authentication failures.
+///
+/// An error is returned once the counter is exhausted rather than wrapping
+/// around and reusing a nonce.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NonceCounter {
+    next: Option<Nonce>,
+    endian: Endian,
+}
+
+impl NonceCounter {
+    /// Create a new little-endian [`NonceCounter`] starting at the given
+    /// nonce.
+    pub fn new(initial: Nonce) -> Self {
+        Self::with_endian(initial, Endian::Little)
+    }
+
+    /// Create a new [`NonceCounter`] starting at the given nonce, which is
+    /// incremented in the given byte order.
+    pub fn with_endian(initial: Nonce, endian: Endian) -> Self {
+        Self {
+            next: Some(initial),
+            endian,
+        }
+    }
+
+    /// Get the next nonce, advancing the counter.
+    pub fn next_nonce(&mut self) -> Result<Nonce, Error> {
+        let nonce = self.next.take().ok_or(Error)?;
+
+        let mut next = nonce;
+        let overflow = match self.endian {
+            Endian::Big => increment(next.iter_mut().rev()),
+            Endian::Little => increment(next.iter_mut()),
+        };
+        if !overflow {
+            self.next = Some(next);
+        }
+
+        Ok(nonce)
+    }
+}
+
+/// Increment the integer made of the given bytes, from least to most
+/// significant, returning whether it overflowed.
+fn increment<'a>(bytes: impl Iterator<Item = &'a mut u8>) -> bool {
+    for byte in bytes {
+        let (value, overflow) = byte.overflowing_add(1);
+        *byte = value;
+
+        if !overflow {
+            return false;
+        }
+    }
+
+    true
+}
+
+impl Default for NonceCounter {
+    fn default() -> Self {
+        Self::new(Nonce::default())
+    }
+}
+
+/// Build a nonce from a fixed 16-byte session context and 8 per-message
+/// random bytes, laid out as `context || random`.
+///
+/// This allows only the random bytes to be transmitted with each message,
+/// with both ends deriving the same context for a session. Nonces are unique
+/// within a session as long as the random parts are: with 8 random bytes,
+/// a collision becomes likely after about 2<sup>32</sup> messages, so
+/// sessions should be rekeyed well before that.
+pub fn nonce_with_fixed_context(context: &[u8; 16], random: &[u8; 8]) -> Nonce {
+    let mut nonce = Nonce::default();
+    nonce[..context.len()].copy_from_slice(context);
+    nonce[context.len()..].copy_from_slice(random);
+    nonce
+}
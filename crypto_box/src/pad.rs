@@ -0,0 +1,59 @@
+//! Length-hiding padding of messages prior to encryption.
+//!
+//! Ciphertexts reveal the length of the plaintext they encrypt. Padding
+//! messages to a multiple of a block size before encrypting them hides their
+//! exact length.
+//!
+//! This uses the ISO/IEC 7816-4 padding scheme (a `0x80` byte followed by
+//! zeroes) and is compatible with libsodium's `sodium_pad`/`sodium_unpad`.
+
+use alloc::vec::Vec;
+use xsalsa20poly1305::aead::Error;
+
+/// Pad the given message to a multiple of `block_size` bytes.
+///
+/// At least one byte of padding is always added, so a message whose length
+/// is already a multiple of `block_size` is padded by a full block.
+///
+/// # Panics
+///
+/// Panics if `block_size` is zero.
+pub fn pad_message(message: &[u8], block_size: usize) -> Vec<u8> {
+    assert!(block_size > 0, "block size must be non-zero");
+
+    let padded_len = message.len() + block_size - message.len() % block_size;
+    let mut padded = Vec::with_capacity(padded_len);
+    padded.extend_from_slice(message);
+    padded.push(0x80);
+    padded.resize(padded_len, 0);
+    padded
+}
+
+/// Remove padding added by [`pad_message`] with the same `block_size`.
+///
+/// The padding is located in constant time with respect to its contents.
+/// Returns an error if the padding is invalid.
+pub fn unpad_message(padded: &[u8], block_size: usize) -> Result<&[u8], Error> {
+    if block_size == 0 || padded.len() < block_size {
+        return Err(Error);
+    }
+
+    let mut acc = 0usize;
+    let mut pad_len = 0usize;
+    let mut valid = 0u8;
+
+    for i in 0..block_size {
+        let c = padded[padded.len() - 1 - i] as usize;
+        let is_barrier =
+            ((acc.wrapping_sub(1) & pad_len.wrapping_sub(1) & (c ^ 0x80).wrapping_sub(1)) >> 8) & 1;
+        acc |= c;
+        pad_len |= i & is_barrier.wrapping_neg();
+        valid |= is_barrier as u8;
+    }
+
+    if valid == 0 {
+        return Err(Error);
+    }
+
+    Ok(&padded[..padded.len() - 1 - pad_len])
+}
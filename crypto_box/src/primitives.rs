@@ -0,0 +1,38 @@
+//! Re-exports of the raw primitives used by this crate, so that advanced
+//! users can rely on the exact versions it depends on.
+//!
+//! For the key derivations performed by the box constructors, see
+//! [`hazmat`][`crate::hazmat`] instead.
+//!
+//! ```rust
+//! # #[cfg(feature = "std")]
+//! # {
+//! use crypto_box::{
+//!     aead::{generic_array::GenericArray, Aead},
+//!     primitives::hsalsa20,
+//!     DiffieHellman, SalsaBox, SecretKey,
+//! };
+//!
+//! let mut rng = crypto_box::rand_core::OsRng;
+//! let alice_secret_key = SecretKey::generate(&mut rng);
+//! let bob_public_key = SecretKey::generate(&mut rng).public_key();
+//!
+//! // Derive the key from the X25519 shared secret as `SalsaBox::new` does
+//! let shared_secret = alice_secret_key.diffie_hellman(&bob_public_key);
+//! let derived = hsalsa20(
+//!     GenericArray::from_slice(&shared_secret),
+//!     &GenericArray::default(),
+//! );
+//!
+//! let nonce = crypto_box::generate_nonce(&mut rng);
+//! assert_eq!(
+//!     SalsaBox::from_key(&derived).encrypt(&nonce, &b"message"[..]).unwrap(),
+//!     SalsaBox::new(&bob_public_key, &alice_secret_key)
+//!         .encrypt(&nonce, &b"message"[..])
+//!         .unwrap(),
+//! );
+//! # }
+//! ```
+
+pub use chacha20::{hchacha, R20};
+pub use salsa20::hsalsa20;
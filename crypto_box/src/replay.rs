@@ -0,0 +1,97 @@
+//! Replay protection for numbered messages.
+
+use alloc::{vec, vec::Vec};
+
+/// Sliding window of received message numbers, used to reject replayed and
+/// too-old messages in datagram protocols where messages may arrive out of
+/// order.
+///
+/// The message number must be authenticated along with each message, e.g. by
+/// passing its little endian encoding as the associated data of a
+/// [`ChaChaBox`]. Only call [`ReplayWindow::check_and_update`] once the
+/// message has been successfully decrypted, so that forged messages can't
+/// advance the window.
+///
+/// [`ChaChaBox`]: crate::ChaChaBox
+#[derive(Clone, Debug)]
+pub struct ReplayWindow {
+    /// Bit `i` is set if message number `highest - i` has been received.
+    bitmap: Vec<u64>,
+    size: u64,
+    highest: Option<u64>,
+}
+
+impl ReplayWindow {
+    /// Create a new [`ReplayWindow`] accepting messages up to `size - 1`
+    /// behind the highest message number received so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "window size must be non-zero");
+
+        ReplayWindow {
+            bitmap: vec![0; (size - 1) / 64 + 1],
+            size: size as u64,
+            highest: None,
+        }
+    }
+
+    /// Check whether the given message number is acceptable, i.e. neither
+    /// a replay nor too old, and if so record it as received.
+    pub fn check_and_update(&mut self, seq: u64) -> bool {
+        let highest = match self.highest {
+            Some(highest) => highest,
+            None => {
+                self.highest = Some(seq);
+                self.bitmap[0] = 1;
+                return true;
+            }
+        };
+
+        if seq > highest {
+            self.shift(seq - highest);
+            self.bitmap[0] |= 1;
+            self.highest = Some(seq);
+            return true;
+        }
+
+        let age = highest - seq;
+        if age >= self.size {
+            return false;
+        }
+
+        let (word, bit) = ((age / 64) as usize, age % 64);
+        if self.bitmap[word] >> bit & 1 == 1 {
+            return false;
+        }
+
+        self.bitmap[word] |= 1 << bit;
+        true
+    }
+
+    /// Shift the window forward by `n` message numbers.
+    fn shift(&mut self, n: u64) {
+        if n >= self.size {
+            self.bitmap.iter_mut().for_each(|word| *word = 0);
+            return;
+        }
+
+        let (word_shift, bit_shift) = ((n / 64) as usize, (n % 64) as u32);
+
+        for i in (0..self.bitmap.len()).rev() {
+            self.bitmap[i] = if i < word_shift {
+                0
+            } else {
+                let src = i - word_shift;
+                let carry = if bit_shift > 0 && src > 0 {
+                    self.bitmap[src - 1] >> (64 - bit_shift)
+                } else {
+                    0
+                };
+                self.bitmap[src] << bit_shift | carry
+            };
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Fixed-capacity buffer for in-place encryption without an allocator.
+
+use xsalsa20poly1305::aead::{Buffer, Error};
+use zeroize::Zeroize;
+
+/// Fixed-capacity [`Buffer`] backed by an array, for repeatedly encrypting
+/// and decrypting messages in place without an allocator.
+///
+/// A message and its tag must fit in `N` bytes: extending the buffer beyond
+/// its capacity fails with an error. The contents are zeroized when the
+/// buffer is truncated, cleared or dropped.
+pub struct ScratchBuffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ScratchBuffer<N> {
+    /// Create a new empty [`ScratchBuffer`].
+    pub fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Get the capacity of the buffer, in bytes.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Zeroize the contents of the buffer and reset its length, ready for the
+    /// next message.
+    pub fn clear(&mut self) {
+        self.bytes[..self.len].zeroize();
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for ScratchBuffer<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for ScratchBuffer<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes[..self.len]
+    }
+}
+
+impl<const N: usize> Buffer for ScratchBuffer<N> {
+    fn extend_from_slice(&mut self, other: &[u8]) -> Result<(), Error> {
+        let end = self
+            .len
+            .checked_add(other.len())
+            .filter(|&end| end <= N)
+            .ok_or(Error)?;
+
+        self.bytes[self.len..end].copy_from_slice(other);
+        self.len = end;
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        // Decrypting in place may leave a copy of the end of the plaintext
+        // past the new length
+        if len < self.len {
+            self.bytes[len..self.len].zeroize();
+            self.len = len;
+        }
+    }
+}
+
+impl<const N: usize> Default for ScratchBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Drop for ScratchBuffer<N> {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScratchBuffer;
+    use crate::{Nonce, PrecomputedKey, SalsaBox};
+    use xsalsa20poly1305::aead::{AeadInPlace, Buffer};
+
+    #[test]
+    fn clear_after_decrypt_in_place() {
+        let salsa_box = SalsaBox::from(&PrecomputedKey::from([1; 32]));
+        let nonce = Nonce::default();
+        let mut buffer = ScratchBuffer::<64>::new();
+
+        buffer.extend_from_slice(&[0xaa; 32]).unwrap();
+        salsa_box
+            .encrypt_in_place(&nonce, b"", &mut buffer)
+            .unwrap();
+        salsa_box
+            .decrypt_in_place(&nonce, b"", &mut buffer)
+            .unwrap();
+        assert_eq!(buffer.as_ref(), &[0xaa; 32][..]);
+
+        // The plaintext is shifted over the tag, and nothing is left past it
+        assert!(buffer.bytes[32..].iter().all(|&b| b == 0));
+
+        buffer.clear();
+        assert!(buffer.bytes.iter().all(|&b| b == 0));
+    }
+}
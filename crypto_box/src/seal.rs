@@ -0,0 +1,281 @@
+//! Anonymous sealed boxes.
+//!
+//! Sealed boxes allow a message to be encrypted to a recipient's
+//! [`PublicKey`] anonymously: the sender generates an ephemeral keypair for
+//! each message, so the recipient can't identify (or authenticate) the
+//! sender.
+//!
+//! The construction is compatible with libsodium's `crypto_box_seal`:
+//!
+//! - ciphertext = `ephemeral_pk || box(message, nonce, recipient_pk, ephemeral_sk)`
+//! - nonce = `BLAKE2b-192(ephemeral_pk || recipient_pk)`
+//!
+//! The allocating functions require the `seal` feature. The in-place
+//! functions ([`SealedBox::seal_in_place`] and [`SealedBox::open_in_place`])
+//! only require `blake2`, and work with any [`Buffer`], e.g. a
+//! `heapless::Vec`, as does [`SealedBox::seal_const`] which seals into a
+//! fixed-size array.
+
+use crate::{Nonce, PublicKey, SalsaBox, SecretKey, Tag, KEY_SIZE, TAG_SIZE};
+use blake2::{digest::Digest, Blake2b};
+use rand_core::{CryptoRng, RngCore};
+use xsalsa20poly1305::aead::{consts::U24, AeadInPlace, Buffer, Error};
+use zeroize::Zeroize;
+
+#[cfg(feature = "seal")]
+use {alloc::vec::Vec, xsalsa20poly1305::aead::Aead};
+
+/// Size of the sealed box overhead: the ephemeral public key and the tag.
+const SEAL_OVERHEAD: usize = KEY_SIZE + TAG_SIZE;
+
+/// Anonymous sealed box functions, compatible with libsodium's
+/// `crypto_box_seal` and `crypto_box_seal_open`.
+///
+/// This type is not constructible: it only serves to group the functions
+/// which operate on sealed boxes.
+pub enum SealedBox {}
+
+#[cfg(feature = "seal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "seal")))]
+impl SealedBox {
+    /// Seal the given plaintext to the recipient's [`PublicKey`], using a
+    /// freshly generated ephemeral keypair.
+    pub fn seal<T>(
+        csprng: &mut T,
+        recipient_pk: &PublicKey,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Error>
+    where
+        T: RngCore + CryptoRng,
+    {
+        let (ephemeral_pk, ciphertext) = Self::seal_detached(csprng, recipient_pk, plaintext)?;
+
+        let mut sealed = Vec::with_capacity(KEY_SIZE + ciphertext.len());
+        sealed.extend_from_slice(ephemeral_pk.as_bytes());
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Seal the given plaintext to the recipient's [`PublicKey`], returning
+    /// the ephemeral [`PublicKey`] separately from the ciphertext.
+    ///
+    /// Concatenating the ephemeral public key and the ciphertext gives the
+    /// same output as [`SealedBox::seal`].
+    pub fn seal_detached<T>(
+        csprng: &mut T,
+        recipient_pk: &PublicKey,
+        plaintext: &[u8],
+    ) -> Result<(PublicKey, Vec<u8>), Error>
+    where
+        T: RngCore + CryptoRng,
+    {
+        let ephemeral_sk = SecretKey::generate(csprng);
+        let ephemeral_pk = ephemeral_sk.public_key();
+        let nonce = derive_nonce(&ephemeral_pk, recipient_pk);
+
+        let ciphertext = SalsaBox::new(recipient_pk, &ephemeral_sk).encrypt(&nonce, plaintext)?;
+        Ok((ephemeral_pk, ciphertext))
+    }
+
+    /// Seal the given plaintext to a recipient identified by their Ed25519
+    /// public key, converting it into an X25519 [`PublicKey`] first (see
+    /// [`PublicKey::from_ed25519`]).
+    ///
+    /// The recipient can open the sealed box with the X25519 [`SecretKey`]
+    /// converted from their Ed25519 secret key.
+    #[cfg(feature = "ed25519")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ed25519")))]
+    pub fn seal_to_ed25519<T>(
+        csprng: &mut T,
+        ed25519_pk: &[u8; KEY_SIZE],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, crate::Error>
+    where
+        T: RngCore + CryptoRng,
+    {
+        let recipient_pk = PublicKey::from_ed25519(ed25519_pk)?;
+        Ok(Self::seal(csprng, &recipient_pk, plaintext)?)
+    }
+
+    /// Open a sealed box using the recipient's [`SecretKey`].
+    pub fn open(recipient_sk: &SecretKey, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        Self::open_with_sender(recipient_sk, ciphertext).map(|(_, plaintext)| plaintext)
+    }
+
+    /// Open a sealed box using the recipient's [`SecretKey`], also returning
+    /// the ephemeral [`PublicKey`] the sender used to seal it.
+    pub fn open_with_sender(
+        recipient_sk: &SecretKey,
+        ciphertext: &[u8],
+    ) -> Result<(PublicKey, Vec<u8>), Error> {
+        if ciphertext.len() < KEY_SIZE {
+            return Err(Error);
+        }
+
+        let (ephemeral_pk, ciphertext) = ciphertext.split_at(KEY_SIZE);
+        let mut ephemeral_pk_bytes = [0u8; KEY_SIZE];
+        ephemeral_pk_bytes.copy_from_slice(ephemeral_pk);
+        let ephemeral_pk = PublicKey::from(ephemeral_pk_bytes);
+
+        let plaintext = Self::open_detached(recipient_sk, &ephemeral_pk, ciphertext)?;
+        Ok((ephemeral_pk, plaintext))
+    }
+
+    /// Open a sealed box whose ephemeral [`PublicKey`] was transmitted
+    /// separately from the ciphertext, as produced by
+    /// [`SealedBox::seal_detached`].
+    pub fn open_detached(
+        recipient_sk: &SecretKey,
+        ephemeral_pk: &PublicKey,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let nonce = derive_nonce(ephemeral_pk, &recipient_sk.public_key());
+        SalsaBox::new(ephemeral_pk, recipient_sk).decrypt(&nonce, ciphertext)
+    }
+}
+
+impl SealedBox {
+    /// Get the length of the sealed box of a plaintext of the given length,
+    /// e.g. to size buffers for [`SealedBox::seal_in_place`].
+    ///
+    /// This is the plaintext length plus 48 bytes, for the 32-byte ephemeral
+    /// public key and the 16-byte tag.
+    pub const fn sealed_len(plaintext_len: usize) -> usize {
+        SEAL_OVERHEAD + plaintext_len
+    }
+
+    /// Check whether the given data could be a sealed box.
+    ///
+    /// This is a cheap heuristic, meant as a pre-filter e.g. to tell sealed
+    /// boxes apart from other messages: the data must be long enough to hold
+    /// the ephemeral public key and the tag, and the ephemeral public key
+    /// must not be a point of small order (see [`PublicKey::is_valid_ct`]).
+    ///
+    /// This does **not** authenticate the data: only opening the sealed box
+    /// does.
+    pub fn looks_like_sealed(data: &[u8]) -> bool {
+        if data.len() < SEAL_OVERHEAD {
+            return false;
+        }
+
+        let mut ephemeral_pk_bytes = [0u8; KEY_SIZE];
+        ephemeral_pk_bytes.copy_from_slice(&data[..KEY_SIZE]);
+        PublicKey::from(ephemeral_pk_bytes).is_valid_ct().into()
+    }
+
+    /// Seal the plaintext in the given buffer to the recipient's
+    /// [`PublicKey`] in place, using a freshly generated ephemeral keypair.
+    ///
+    /// The buffer is grown by 48 bytes: on success it holds the same output
+    /// as [`SealedBox::seal`], i.e. the ephemeral public key, followed by the
+    /// tag and the ciphertext. The buffer must have enough capacity for this,
+    /// otherwise an error is returned.
+    pub fn seal_in_place<T>(
+        csprng: &mut T,
+        recipient_pk: &PublicKey,
+        buffer: &mut dyn Buffer,
+    ) -> Result<(), Error>
+    where
+        T: RngCore + CryptoRng,
+    {
+        let len = buffer.len();
+        buffer.extend_from_slice(&[0u8; SEAL_OVERHEAD])?;
+        buffer.as_mut().copy_within(..len, SEAL_OVERHEAD);
+
+        let result = seal_into(csprng, recipient_pk, buffer.as_mut());
+        if result.is_err() {
+            buffer.as_mut().copy_within(SEAL_OVERHEAD.., 0);
+            buffer.truncate(len);
+        }
+
+        result
+    }
+
+    /// Seal the given plaintext to the recipient's [`PublicKey`] into a
+    /// fixed-size array, without allocating.
+    ///
+    /// Returns the array along with the length of the sealed box it holds,
+    /// which is the same output as [`SealedBox::seal`]. The capacity `N`
+    /// must be at least 48 bytes more than the length of the plaintext,
+    /// otherwise [`Error::BufferTooSmall`](crate::Error::BufferTooSmall) is
+    /// returned.
+    pub fn seal_const<T, const N: usize>(
+        csprng: &mut T,
+        recipient_pk: &PublicKey,
+        plaintext: &[u8],
+    ) -> Result<([u8; N], usize), crate::Error>
+    where
+        T: RngCore + CryptoRng,
+    {
+        let required = SEAL_OVERHEAD + plaintext.len();
+        if N < required {
+            return Err(crate::Error::BufferTooSmall {
+                required,
+                capacity: N,
+            });
+        }
+
+        let mut sealed = [0u8; N];
+        sealed[SEAL_OVERHEAD..required].copy_from_slice(plaintext);
+
+        if let Err(err) = seal_into(csprng, recipient_pk, &mut sealed[..required]) {
+            sealed[..].zeroize();
+            return Err(err.into());
+        }
+
+        Ok((sealed, required))
+    }
+
+    /// Open a sealed box in place using the recipient's [`SecretKey`].
+    ///
+    /// On success the buffer is shrunk by 48 bytes and holds the plaintext.
+    /// On failure the buffer is left unmodified.
+    pub fn open_in_place(recipient_sk: &SecretKey, buffer: &mut dyn Buffer) -> Result<(), Error> {
+        let len = buffer.len();
+        if len < SEAL_OVERHEAD {
+            return Err(Error);
+        }
+
+        let (header, ciphertext) = buffer.as_mut().split_at_mut(SEAL_OVERHEAD);
+        let mut ephemeral_pk_bytes = [0u8; KEY_SIZE];
+        ephemeral_pk_bytes.copy_from_slice(&header[..KEY_SIZE]);
+        let ephemeral_pk = PublicKey::from(ephemeral_pk_bytes);
+        let tag = *Tag::from_slice(&header[KEY_SIZE..]);
+
+        let nonce = derive_nonce(&ephemeral_pk, &recipient_sk.public_key());
+        SalsaBox::new(&ephemeral_pk, recipient_sk)
+            .decrypt_in_place_detached(&nonce, b"", ciphertext, &tag)?;
+
+        buffer.as_mut().copy_within(SEAL_OVERHEAD.., 0);
+        buffer.truncate(len - SEAL_OVERHEAD);
+        Ok(())
+    }
+}
+
+/// Seal the plaintext following the first 48 bytes of `sealed`, writing the
+/// ephemeral public key and the tag into those 48 bytes.
+fn seal_into<T>(csprng: &mut T, recipient_pk: &PublicKey, sealed: &mut [u8]) -> Result<(), Error>
+where
+    T: RngCore + CryptoRng,
+{
+    let ephemeral_sk = SecretKey::generate(csprng);
+    let ephemeral_pk = ephemeral_sk.public_key();
+    let nonce = derive_nonce(&ephemeral_pk, recipient_pk);
+
+    let (header, plaintext) = sealed.split_at_mut(SEAL_OVERHEAD);
+    let tag = SalsaBox::new(recipient_pk, &ephemeral_sk)
+        .encrypt_in_place_detached(&nonce, b"", plaintext)?;
+
+    header[..KEY_SIZE].copy_from_slice(ephemeral_pk.as_bytes());
+    header[KEY_SIZE..].copy_from_slice(&tag);
+    Ok(())
+}
+
+/// Derive the nonce for a sealed box from the ephemeral and recipient public
+/// keys.
+fn derive_nonce(ephemeral_pk: &PublicKey, recipient_pk: &PublicKey) -> Nonce {
+    let mut hasher = Blake2b::<U24>::new();
+    hasher.update(ephemeral_pk.as_bytes());
+    hasher.update(recipient_pk.as_bytes());
+    hasher.finalize()
+}
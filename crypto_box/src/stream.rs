@@ -0,0 +1,430 @@
+//! Streaming encryption over [`std::io`].
+//!
+//! Messages are split into fixed-size chunks which are encrypted using the
+//! [STREAM] construction ([`StreamBE32`]): each chunk's nonce is made of a
+//! random 19-byte prefix (written as the stream header), a 32-bit big endian
+//! chunk counter and a last-chunk flag. This prevents chunks from being
+//! reordered, dropped or truncated without detection.
+//!
+//! The stream format is:
+//!
+//! - header = 19-byte random nonce prefix
+//! - zero or more full chunks of `chunk_size` bytes of plaintext (plus tag)
+//! - a final chunk of strictly less than `chunk_size` bytes of plaintext
+//!   (plus tag), which may be empty
+//!
+//! Associated data supplied when creating the stream (e.g. a file header) is
+//! bound into the first chunk, which authenticates it for the whole stream.
+//! Since [`SalsaBox`] does not support associated data, this requires an AEAD
+//! such as [`ChaChaBox`].
+//!
+//! [STREAM]: https://eprint.iacr.org/2015/189.pdf
+//! [`SalsaBox`]: crate::SalsaBox
+//! [`ChaChaBox`]: crate::ChaChaBox
+
+use crate::aead::{
+    consts::U24,
+    stream::{DecryptorBE32, EncryptorBE32, NewStream, StreamBE32},
+    AeadInPlace,
+};
+use crate::TAG_SIZE;
+use rand_core::{CryptoRng, RngCore};
+use std::{
+    io::{self, Read, Write},
+    vec::Vec,
+};
+
+#[cfg(feature = "blake2")]
+use blake2::{digest::Digest, Blake2b512};
+
+/// Default size of the plaintext of each chunk, in bytes.
+pub const DEFAULT_CHUNK_SIZE: usize = 65536;
+
+/// Size of the stream header, in bytes.
+pub const HEADER_SIZE: usize = 19;
+
+/// Nonce prefix written as the stream header.
+type NoncePrefix<A> = crate::aead::stream::Nonce<A, StreamBE32<A>>;
+
+/// Encrypt a stream of plaintext into an underlying [`Write`].
+///
+/// Plaintext is buffered until a full chunk is available. Call
+/// [`EncryptWriter::finish`] once done to write the final chunk: a stream
+/// which is dropped without being finished will fail to decrypt.
+///
+/// Once writing a chunk has failed, whether because of an error from the
+/// underlying writer or from the AEAD, the stream can't be resumed: every
+/// later call to [`Write::write`], [`Write::flush`] or
+/// [`EncryptWriter::finish`] also fails, with [`io::ErrorKind::BrokenPipe`].
+pub struct EncryptWriter<A, W>
+where
+    A: AeadInPlace<NonceSize = U24>,
+    W: Write,
+{
+    encryptor: Option<EncryptorBE32<A>>,
+    inner: W,
+    buffer: Vec<u8>,
+    aad: Option<Vec<u8>>,
+    chunk_size: usize,
+    failed: bool,
+}
+
+impl<A, W> EncryptWriter<A, W>
+where
+    A: AeadInPlace<NonceSize = U24>,
+    W: Write,
+{
+    /// Create a new [`EncryptWriter`] using [`DEFAULT_CHUNK_SIZE`], writing
+    /// the stream header to `inner`.
+    ///
+    /// The `aad` is authenticated as the associated data of the first chunk.
+    pub fn new<T>(aead: A, csprng: &mut T, aad: &[u8], inner: W) -> io::Result<Self>
+    where
+        T: RngCore + CryptoRng,
+    {
+        Self::with_chunk_size(aead, csprng, aad, inner, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Create a new [`EncryptWriter`] using the given chunk size, writing the
+    /// stream header to `inner`.
+    ///
+    /// The `aad` is authenticated as the associated data of the first chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn with_chunk_size<T>(
+        aead: A,
+        csprng: &mut T,
+        aad: &[u8],
+        mut inner: W,
+        chunk_size: usize,
+    ) -> io::Result<Self>
+    where
+        T: RngCore + CryptoRng,
+    {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+
+        let mut prefix = NoncePrefix::<A>::default();
+        csprng.fill_bytes(&mut prefix);
+        inner.write_all(&prefix)?;
+
+        Ok(EncryptWriter {
+            encryptor: Some(EncryptorBE32::from_stream_primitive(StreamBE32::from_aead(
+                aead, &prefix,
+            ))),
+            inner,
+            buffer: Vec::with_capacity(chunk_size + TAG_SIZE),
+            aad: Some(aad.to_vec()),
+            chunk_size,
+            failed: false,
+        })
+    }
+
+    /// Encrypt and write the final chunk, returning the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.check_failed()?;
+        let encryptor = self.encryptor.take().expect("stream already finished");
+        let aad = self.aad.take().unwrap_or_default();
+        encryptor
+            .encrypt_last_in_place(&aad, &mut self.buffer)
+            .map_err(aead_error)?;
+        self.inner.write_all(&self.buffer)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Encrypt and write the buffered full chunk.
+    ///
+    /// On failure, the buffer is discarded and the writer is left in a
+    /// failed state, in which every later call fails.
+    fn write_chunk(&mut self) -> io::Result<()> {
+        let result = self.try_write_chunk();
+        if result.is_err() {
+            self.buffer.clear();
+            self.encryptor = None;
+            self.failed = true;
+        }
+        result
+    }
+
+    /// Encrypt and write the buffered full chunk.
+    fn try_write_chunk(&mut self) -> io::Result<()> {
+        debug_assert_eq!(self.buffer.len(), self.chunk_size);
+        let aad = self.aad.take().unwrap_or_default();
+        self.encryptor
+            .as_mut()
+            .expect("stream already finished")
+            .encrypt_next_in_place(&aad, &mut self.buffer)
+            .map_err(aead_error)?;
+        self.inner.write_all(&self.buffer)?;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Return an error if writing a previous chunk failed.
+    fn check_failed(&self) -> io::Result<()> {
+        if self.failed {
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "stream failed to encrypt",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<A, W> Write for EncryptWriter<A, W>
+where
+    A: AeadInPlace<NonceSize = U24>,
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_failed()?;
+        let len = buf.len().min(self.chunk_size - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+
+        if self.buffer.len() == self.chunk_size {
+            self.write_chunk()?;
+        }
+
+        Ok(len)
+    }
+
+    /// Flush the underlying writer.
+    ///
+    /// Buffered plaintext is only written once a full chunk is available, or
+    /// when the stream is finished.
+    fn flush(&mut self) -> io::Result<()> {
+        self.check_failed()?;
+        self.inner.flush()
+    }
+}
+
+/// Decrypt a stream of ciphertext from an underlying [`Read`].
+///
+/// The chunk size must match the one used to encrypt the stream.
+///
+/// Only one chunk is buffered at a time, so arbitrarily large streams can be
+/// decrypted in bounded memory. No plaintext is returned from a chunk until
+/// that chunk's tag has been verified. However, this is verification per
+/// chunk rather than for the whole stream: earlier chunks are returned
+/// before later ones are read, so when a stream turns out to be truncated
+/// or corrupted, the plaintext already read must be discarded. The stream
+/// is only fully authenticated once the final chunk has been successfully
+/// read, i.e. once [`Read::read`] returns `Ok(0)` or
+/// [`DecryptReader::copy_to`] returns `Ok`.
+///
+/// Once reading a chunk has failed, whether because it didn't authenticate
+/// or because of an error from the underlying reader, every later read also
+/// fails with [`io::ErrorKind::InvalidData`], so that retrying can't make a
+/// tampered or truncated stream look complete.
+pub struct DecryptReader<A, R>
+where
+    A: AeadInPlace<NonceSize = U24>,
+    R: Read,
+{
+    decryptor: Option<DecryptorBE32<A>>,
+    inner: R,
+    buffer: Vec<u8>,
+    aad: Option<Vec<u8>>,
+    position: usize,
+    chunk_size: usize,
+    failed: bool,
+}
+
+impl<A, R> DecryptReader<A, R>
+where
+    A: AeadInPlace<NonceSize = U24>,
+    R: Read,
+{
+    /// Create a new [`DecryptReader`] using [`DEFAULT_CHUNK_SIZE`], reading
+    /// the stream header from `inner`.
+    ///
+    /// The `aad` must match the one the stream was encrypted with, otherwise
+    /// decrypting the first chunk fails.
+    pub fn new(aead: A, aad: &[u8], inner: R) -> io::Result<Self> {
+        Self::with_chunk_size(aead, aad, inner, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Create a new [`DecryptReader`] using the given chunk size, reading the
+    /// stream header from `inner`.
+    ///
+    /// The `aad` must match the one the stream was encrypted with, otherwise
+    /// decrypting the first chunk fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn with_chunk_size(
+        aead: A,
+        aad: &[u8],
+        mut inner: R,
+        chunk_size: usize,
+    ) -> io::Result<Self> {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+
+        let mut prefix = NoncePrefix::<A>::default();
+        inner.read_exact(&mut prefix)?;
+
+        Ok(DecryptReader {
+            decryptor: Some(DecryptorBE32::from_stream_primitive(StreamBE32::from_aead(
+                aead, &prefix,
+            ))),
+            inner,
+            buffer: Vec::with_capacity(chunk_size + TAG_SIZE),
+            aad: Some(aad.to_vec()),
+            position: 0,
+            chunk_size,
+            failed: false,
+        })
+    }
+
+    /// Decrypt the whole stream into `writer`, one verified chunk at a time,
+    /// returning the number of plaintext bytes written.
+    ///
+    /// Each chunk is written straight from the internal buffer once its tag
+    /// verifies, without additional buffering. If an error is returned, the
+    /// plaintext already written must be discarded (see [`DecryptReader`]).
+    pub fn copy_to<W>(&mut self, writer: &mut W) -> io::Result<u64>
+    where
+        W: Write,
+    {
+        let mut written = 0;
+
+        loop {
+            if self.position == self.buffer.len() {
+                if self.decryptor.is_none() && !self.failed {
+                    return Ok(written);
+                }
+                self.read_chunk()?;
+            }
+
+            writer.write_all(&self.buffer[self.position..])?;
+            written += (self.buffer.len() - self.position) as u64;
+            self.position = self.buffer.len();
+        }
+    }
+
+    /// Read and decrypt the next chunk into the buffer.
+    ///
+    /// On failure, the buffer is discarded and the reader is left in a
+    /// failed state, in which every later call fails.
+    fn read_chunk(&mut self) -> io::Result<()> {
+        if self.failed {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream failed to decrypt",
+            ));
+        }
+
+        let result = self.try_read_chunk();
+        if result.is_err() {
+            self.buffer.clear();
+            self.position = 0;
+            self.decryptor = None;
+            self.failed = true;
+        }
+        result
+    }
+
+    /// Read and decrypt the next chunk into the buffer.
+    ///
+    /// A chunk which is shorter than a full chunk is the final one.
+    fn try_read_chunk(&mut self) -> io::Result<()> {
+        let frame_size = self.chunk_size + TAG_SIZE;
+        self.buffer.resize(frame_size, 0);
+        self.position = 0;
+
+        let mut len = 0;
+        while len < frame_size {
+            match self.inner.read(&mut self.buffer[len..]) {
+                Ok(0) => break,
+                Ok(n) => len += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.buffer.truncate(len);
+
+        let aad = self.aad.take().unwrap_or_default();
+        let result = if len == frame_size {
+            self.decryptor
+                .as_mut()
+                .expect("stream already finished")
+                .decrypt_next_in_place(&aad, &mut self.buffer)
+        } else {
+            self.decryptor
+                .take()
+                .expect("stream already finished")
+                .decrypt_last_in_place(&aad, &mut self.buffer)
+        };
+
+        result.map_err(aead_error)
+    }
+}
+
+impl<A, R> Read for DecryptReader<A, R>
+where
+    A: AeadInPlace<NonceSize = U24>,
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.buffer.len() {
+            if self.decryptor.is_none() && !self.failed {
+                return Ok(0);
+            }
+            self.read_chunk()?;
+        }
+
+        let len = buf.len().min(self.buffer.len() - self.position);
+        buf[..len].copy_from_slice(&self.buffer[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Encrypt all of `plaintext` into `writer` as a stream (see
+/// [`EncryptWriter`]), computing a BLAKE2b-512 hash of the plaintext on the
+/// way.
+///
+/// Returns the underlying writer along with the plaintext hash, which can be
+/// used to verify content identity independently of the AEAD tags.
+#[cfg(feature = "blake2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blake2")))]
+pub fn encrypt_hashed<A, T, R, W>(
+    aead: A,
+    csprng: &mut T,
+    mut plaintext: R,
+    writer: W,
+) -> io::Result<(W, [u8; 64])>
+where
+    A: AeadInPlace<NonceSize = U24>,
+    T: RngCore + CryptoRng,
+    R: Read,
+    W: Write,
+{
+    let mut writer = EncryptWriter::new(aead, csprng, b"", writer)?;
+    let mut hasher = Blake2b512::new();
+    let mut buf = [0u8; 8192];
+
+    loop {
+        let len = match plaintext.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buf[..len]);
+        writer.write_all(&buf[..len])?;
+    }
+
+    let mut hash = [0u8; 64];
+    hash.copy_from_slice(&hasher.finalize());
+    Ok((writer.finish()?, hash))
+}
+
+/// Convert an AEAD error into an I/O error.
+fn aead_error(err: crate::aead::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
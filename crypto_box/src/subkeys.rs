@@ -0,0 +1,57 @@
+//! Separate encryption and authentication keys derived from a box.
+
+use crate::KEY_SIZE;
+use core::fmt::{self, Debug};
+use zeroize::Zeroize;
+
+/// Encryption key derived by [`SalsaBox::derive_enc_mac_keys`].
+///
+/// The key bytes are zeroized when the key is dropped.
+///
+/// [`SalsaBox::derive_enc_mac_keys`]: crate::SalsaBox::derive_enc_mac_keys
+pub struct EncKey(pub(crate) [u8; KEY_SIZE]);
+
+impl EncKey {
+    /// Get the bytes of the [`EncKey`].
+    pub fn as_bytes(&self) -> &[u8; KEY_SIZE] {
+        &self.0
+    }
+}
+
+impl Debug for EncKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncKey(...)")
+    }
+}
+
+impl Drop for EncKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Authentication key derived by [`SalsaBox::derive_enc_mac_keys`].
+///
+/// The key bytes are zeroized when the key is dropped.
+///
+/// [`SalsaBox::derive_enc_mac_keys`]: crate::SalsaBox::derive_enc_mac_keys
+pub struct MacKey(pub(crate) [u8; KEY_SIZE]);
+
+impl MacKey {
+    /// Get the bytes of the [`MacKey`].
+    pub fn as_bytes(&self) -> &[u8; KEY_SIZE] {
+        &self.0
+    }
+}
+
+impl Debug for MacKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MacKey(...)")
+    }
+}
+
+impl Drop for MacKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
@@ -0,0 +1,39 @@
+//! Runtime enumeration of the box variants supported by this build.
+
+/// Box variant (cipher suite) provided by this crate.
+///
+/// All variants are always defined, so that they can be matched on
+/// regardless of which features are enabled. Use [`supported_suites`] to
+/// find out which ones are available.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Suite {
+    /// [`SalsaBox`][`crate::SalsaBox`]: X25519 with XSalsa20Poly1305.
+    XSalsa20Poly1305,
+
+    /// [`ChaChaBox`][`crate::ChaChaBox`]: X25519 with XChaCha20Poly1305.
+    XChaCha20Poly1305,
+
+    /// [`IetfChaChaBox`][`crate::IetfChaChaBox`]: X25519 with the IETF
+    /// ChaCha20Poly1305 and 96-bit nonces.
+    IetfChaCha20Poly1305,
+
+    /// Anonymous sealed boxes using X25519 with XSalsa20Poly1305, compatible
+    /// with libsodium's `crypto_box_seal`. Requires the `seal` feature.
+    SealedXSalsa20Poly1305,
+}
+
+/// Suites supported by this build.
+const SUPPORTED_SUITES: &[Suite] = &[
+    Suite::XSalsa20Poly1305,
+    Suite::XChaCha20Poly1305,
+    Suite::IetfChaCha20Poly1305,
+    #[cfg(feature = "seal")]
+    Suite::SealedXSalsa20Poly1305,
+];
+
+/// Get the [`Suite`]s supported by this build, depending on the enabled
+/// features, e.g. for advertising them during protocol negotiation.
+pub fn supported_suites() -> &'static [Suite] {
+    SUPPORTED_SUITES
+}
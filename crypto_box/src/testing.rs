@@ -0,0 +1,27 @@
+//! Deterministic helpers for differential testing against other
+//! implementations, e.g. fuzzing against libsodium.
+//!
+//! These take raw byte arrays and don't use any RNG, so that identical inputs
+//! can be fed to both implementations and their outputs compared. They are
+//! not meant for production use: in particular, the caller is responsible for
+//! never reusing a nonce.
+
+use crate::{PublicKey, SalsaBox, SecretKey, KEY_SIZE};
+use alloc::vec::Vec;
+use xsalsa20poly1305::aead::{generic_array::GenericArray, Aead};
+
+/// Encrypt the plaintext from `sk_bytes` to `pk_bytes` under the given
+/// nonce, with the same output as libsodium's `crypto_box_easy`.
+pub fn encrypt_deterministic(
+    sk_bytes: &[u8; KEY_SIZE],
+    pk_bytes: &[u8; KEY_SIZE],
+    nonce_bytes: &[u8; 24],
+    plaintext: &[u8],
+) -> Vec<u8> {
+    let secret_key = SecretKey::from(*sk_bytes);
+    let public_key = PublicKey::from(*pk_bytes);
+
+    SalsaBox::new(&public_key, &secret_key)
+        .encrypt(GenericArray::from_slice(nonce_bytes), plaintext)
+        .expect("encryption without associated data can't fail")
+}
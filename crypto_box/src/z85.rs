@@ -0,0 +1,91 @@
+//! Z85 encoding of keys, as used by ZeroMQ's CurveZMQ.
+//!
+//! See <https://rfc.zeromq.org/spec/32/>.
+
+use crate::{Error, PublicKey, SecretKey, KEY_SIZE};
+use alloc::string::String;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Length of a Z85-encoded key.
+const Z85_KEY_LEN: usize = KEY_SIZE / 4 * 5;
+
+/// Z85 alphabet.
+const ALPHABET: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+#[cfg_attr(docsrs, doc(cfg(feature = "z85")))]
+impl PublicKey {
+    /// Encode this [`PublicKey`] as a 40-character Z85 string.
+    pub fn to_z85(&self) -> String {
+        encode(&self.0)
+    }
+
+    /// Decode a [`PublicKey`] from a 40-character Z85 string.
+    pub fn from_z85(z85: &str) -> Result<Self, Error> {
+        decode(z85).map(PublicKey)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "z85")))]
+impl SecretKey {
+    /// Encode this [`SecretKey`] as a 40-character Z85 string, which is
+    /// zeroized when dropped.
+    pub fn to_z85(&self) -> Zeroizing<String> {
+        Zeroizing::new(encode(&self.0))
+    }
+
+    /// Decode a [`SecretKey`] from a 40-character Z85 string.
+    pub fn from_z85(z85: &str) -> Result<Self, Error> {
+        decode(z85).map(SecretKey)
+    }
+}
+
+/// Encode a key as Z85.
+fn encode(key: &[u8; KEY_SIZE]) -> String {
+    let mut encoded = String::with_capacity(Z85_KEY_LEN);
+
+    for chunk in key.chunks_exact(4) {
+        let mut value = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let mut digits = [0u8; 5];
+        for digit in digits.iter_mut().rev() {
+            *digit = ALPHABET[(value % 85) as usize];
+            value /= 85;
+        }
+        encoded.extend(digits.iter().map(|&digit| digit as char));
+        digits.zeroize();
+    }
+
+    encoded
+}
+
+/// Decode a Z85-encoded key.
+fn decode(z85: &str) -> Result<[u8; KEY_SIZE], Error> {
+    if z85.len() != Z85_KEY_LEN {
+        return Err(Error::InvalidLength {
+            expected: Z85_KEY_LEN,
+            got: z85.len(),
+        });
+    }
+
+    let mut key = Zeroizing::new([0u8; KEY_SIZE]);
+
+    for (chunk, group) in key.chunks_exact_mut(4).zip(z85.as_bytes().chunks_exact(5)) {
+        let mut value = 0u64;
+        for &c in group {
+            let digit = ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                // The key bytes decoded so far are zeroized with `key`
+                .ok_or(Error::InvalidZ85)?;
+            value = value * 85 + digit as u64;
+        }
+
+        if value > u64::from(u32::MAX) {
+            return Err(Error::InvalidZ85);
+        }
+
+        chunk.copy_from_slice(&(value as u32).to_be_bytes());
+    }
+
+    Ok(*key)
+}
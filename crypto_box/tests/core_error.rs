@@ -0,0 +1,27 @@
+//! Using [`Error`] through `core::error::Error` without `std`.
+
+#![no_std]
+#![cfg(feature = "core_error")]
+
+use core::error::Error as _;
+use crypto_box::Error;
+
+fn source_chain_len(err: &dyn core::error::Error) -> usize {
+    let mut len = 1;
+    let mut source = err.source();
+    while let Some(err) = source {
+        len += 1;
+        source = err.source();
+    }
+    len
+}
+
+#[test]
+fn dyn_core_error() {
+    let err = Error::ZeroNonce;
+    let dyn_err: &dyn core::error::Error = &err;
+
+    assert!(dyn_err.source().is_none());
+    assert_eq!(source_chain_len(dyn_err), 1);
+    assert!(err.source().is_none());
+}
@@ -6,7 +6,7 @@
 #![cfg(feature = "std")]
 
 use crypto_box::aead::{generic_array::GenericArray, Aead, AeadInPlace, Payload};
-use crypto_box::{ChaChaBox, PublicKey, SalsaBox, SecretKey};
+use crypto_box::{ChaChaBox, DiffieHellman, PublicKey, SalsaBox, SecretKey};
 use std::any::TypeId;
 
 // Alice's keypair
@@ -46,6 +46,20 @@ const PLAINTEXT: &[u8] = &[
     0x5e, 0x07, 0x05,
 ];
 
+fn alice_box() -> SalsaBox {
+    SalsaBox::new(
+        &PublicKey::from(BOB_PUBLIC_KEY),
+        &SecretKey::from(ALICE_SECRET_KEY),
+    )
+}
+
+fn bob_box() -> SalsaBox {
+    SalsaBox::new(
+        &PublicKey::from(ALICE_PUBLIC_KEY),
+        &SecretKey::from(BOB_SECRET_KEY),
+    )
+}
+
 #[test]
 fn generate_secret_key() {
     SecretKey::generate(&mut rand_core::OsRng);
@@ -62,6 +76,17 @@ fn secret_and_public_keys() {
     assert_eq!(secret_key.public_key().as_bytes(), &ALICE_PUBLIC_KEY);
 }
 
+#[test]
+fn public_key_matches_variable_base() {
+    // The fixed-base public key matches DH with the basepoint
+    for _ in 0..16 {
+        let secret_key = SecretKey::generate(&mut rand_core::OsRng);
+        let variable_base =
+            x25519_dalek::x25519(*secret_key.as_bytes(), x25519_dalek::X25519_BASEPOINT_BYTES);
+        assert_eq!(secret_key.public_key().as_bytes(), &variable_base);
+    }
+}
+
 macro_rules! impl_tests {
     ($box:ty, $plaintext:expr, $ciphertext:expr) => {
         #[test]
@@ -144,7 +169,7 @@ macro_rules! impl_tests {
 
 mod xsalsa20poly1305 {
     use super::*;
-    const CIPHERTEXT: &[u8] = &[
+    pub(crate) const CIPHERTEXT: &[u8] = &[
         0xc0, 0x3f, 0x27, 0xd1, 0x88, 0xef, 0x65, 0xc, 0xd1, 0x29, 0x36, 0x91, 0x31, 0x37, 0xbb,
         0x17, 0xed, 0x4c, 0x98, 0xc2, 0x64, 0x89, 0x39, 0xe2, 0xe1, 0xd2, 0xe8, 0x55, 0x47, 0xa,
         0x7b, 0x8c, 0x63, 0x2c, 0xab, 0xfd, 0x5a, 0xb3, 0xb3, 0xc2, 0xd3, 0x13, 0xdc, 0x8c, 0x9e,
@@ -162,7 +187,7 @@ mod xsalsa20poly1305 {
 
 mod xchacha20poly1305 {
     use super::*;
-    const CIPHERTEXT: &[u8] = &[
+    pub(crate) const CIPHERTEXT: &[u8] = &[
         0xa2, 0x97, 0x83, 0x74, 0xed, 0x27, 0x9c, 0xfb, 0xcd, 0x6d, 0x6d, 0xa4, 0x27, 0xe4, 0x2d,
         0x4, 0x97, 0xdf, 0xb9, 0x1f, 0xba, 0x87, 0x65, 0x34, 0x93, 0xf4, 0xc8, 0xf1, 0xdd, 0xd9,
         0x64, 0x17, 0xe7, 0x80, 0x12, 0x55, 0xf9, 0x43, 0xd6, 0xe0, 0x7, 0x85, 0xe4, 0xd2, 0x17,
@@ -231,3 +256,2897 @@ mod xchacha20poly1305 {
         assert_eq!(PLAINTEXT, &plaintext[..]);
     }
 }
+
+mod ietf_chacha20poly1305 {
+    use super::*;
+    use ::chacha20poly1305::{aead::NewAead, ChaCha20Poly1305};
+    use crypto_box::{hazmat, IetfChaChaBox, IetfNonce};
+
+    #[test]
+    fn round_trip() {
+        let alice_box = IetfChaChaBox::new(
+            &PublicKey::from(BOB_PUBLIC_KEY),
+            &SecretKey::from(ALICE_SECRET_KEY),
+        );
+        let bob_box = IetfChaChaBox::new(
+            &PublicKey::from(ALICE_PUBLIC_KEY),
+            &SecretKey::from(BOB_SECRET_KEY),
+        );
+        let nonce = IetfNonce::from_slice(&NONCE[..12]);
+
+        let ciphertext = alice_box.encrypt(nonce, PLAINTEXT).unwrap();
+        assert_eq!(ciphertext.len(), PLAINTEXT.len() + 16);
+        assert_eq!(bob_box.decrypt(nonce, &ciphertext[..]).unwrap(), PLAINTEXT);
+
+        // Matches IETF ChaCha20Poly1305 keyed with the HChaCha20-derived key
+        let shared_secret =
+            SecretKey::from(ALICE_SECRET_KEY).diffie_hellman(&PublicKey::from(BOB_PUBLIC_KEY));
+        let key = hazmat::hchacha_derive(&shared_secret);
+        let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(&key));
+        assert_eq!(cipher.encrypt(nonce, PLAINTEXT).unwrap(), ciphertext);
+
+        let mut tampered = ciphertext;
+        tampered[0] ^= 1;
+        assert!(bob_box.decrypt(nonce, &tampered[..]).is_err());
+    }
+}
+
+#[test]
+fn encrypt_with_nonce() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+
+    let (nonce, ciphertext) = alice_box
+        .encrypt_with_nonce(&mut rand_core::OsRng, PLAINTEXT)
+        .unwrap();
+    let plaintext = bob_box.decrypt(&nonce, &ciphertext[..]).unwrap();
+
+    assert_eq!(PLAINTEXT, &plaintext[..]);
+}
+
+#[test]
+fn decrypt_ct() {
+    let bob_box = bob_box();
+    let nonce = GenericArray::from_slice(NONCE);
+    let ciphertext = xsalsa20poly1305::CIPHERTEXT;
+
+    let (choice, plaintext) = bob_box.decrypt_ct(nonce, ciphertext);
+    assert!(bool::from(choice));
+    assert_eq!(bob_box.decrypt(nonce, ciphertext).unwrap(), plaintext);
+
+    let mut tampered = ciphertext.to_vec();
+    tampered[0] ^= 1;
+    let (choice, _) = bob_box.decrypt_ct(nonce, &tampered[..]);
+    assert!(!bool::from(choice));
+    assert!(bob_box.decrypt(nonce, &tampered[..]).is_err());
+}
+
+/// Mock of a secret key held in e.g. a hardware token.
+struct SoftwareToken(SecretKey);
+
+impl DiffieHellman for SoftwareToken {
+    fn diffie_hellman(&self, public_key: &PublicKey) -> [u8; 32] {
+        self.0.diffie_hellman(public_key)
+    }
+}
+
+#[test]
+fn diffie_hellman_trait() {
+    let token = SoftwareToken(SecretKey::from(ALICE_SECRET_KEY));
+    assert_eq!(
+        DiffieHellman::public_key(&token).as_bytes(),
+        &ALICE_PUBLIC_KEY
+    );
+
+    let token_box = SalsaBox::new(&PublicKey::from(BOB_PUBLIC_KEY), &token);
+    let nonce = GenericArray::from_slice(NONCE);
+    let ciphertext = token_box.encrypt(nonce, PLAINTEXT).unwrap();
+
+    assert_eq!(xsalsa20poly1305::CIPHERTEXT, &ciphertext[..]);
+}
+
+#[test]
+fn hazmat_derive() {
+    use ::xsalsa20poly1305::{aead::NewAead, XSalsa20Poly1305};
+    use chacha20poly1305::XChaCha20Poly1305;
+    use crypto_box::hazmat::{hchacha_derive, hsalsa_derive};
+
+    let secret_key = SecretKey::from(ALICE_SECRET_KEY);
+    let public_key = PublicKey::from(BOB_PUBLIC_KEY);
+    let shared_secret = secret_key.diffie_hellman(&public_key);
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let salsa_key = hsalsa_derive(&shared_secret);
+    let ciphertext = XSalsa20Poly1305::new(GenericArray::from_slice(&salsa_key))
+        .encrypt(nonce, PLAINTEXT)
+        .unwrap();
+    assert_eq!(
+        SalsaBox::new(&public_key, &secret_key)
+            .encrypt(nonce, PLAINTEXT)
+            .unwrap(),
+        ciphertext
+    );
+
+    let chacha_key = hchacha_derive(&shared_secret);
+    let ciphertext = XChaCha20Poly1305::new(GenericArray::from_slice(&chacha_key))
+        .encrypt(nonce, PLAINTEXT)
+        .unwrap();
+    assert_eq!(
+        ChaChaBox::new(&public_key, &secret_key)
+            .encrypt(nonce, PLAINTEXT)
+            .unwrap(),
+        ciphertext
+    );
+}
+
+#[test]
+fn encrypt_with_context() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let ciphertext = alice_box
+        .encrypt_with_context(nonce, b"context", PLAINTEXT)
+        .unwrap();
+    let plaintext = bob_box
+        .decrypt_with_context(nonce, b"context", &ciphertext)
+        .unwrap();
+    assert_eq!(PLAINTEXT, &plaintext[..]);
+
+    assert!(bob_box
+        .decrypt_with_context(nonce, b"other context", &ciphertext)
+        .is_err());
+    assert!(bob_box
+        .decrypt_with_context(nonce, b"", &ciphertext)
+        .is_err());
+}
+
+#[test]
+fn verify() {
+    let bob_box = bob_box();
+    let nonce = GenericArray::from_slice(NONCE);
+    let ciphertext = xsalsa20poly1305::CIPHERTEXT;
+
+    assert!(bob_box.verify(nonce, ciphertext).is_ok());
+
+    let mut tampered = ciphertext.to_vec();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(bob_box.verify(nonce, &tampered).is_err());
+    assert!(bob_box.verify(nonce, &ciphertext[..15]).is_err());
+}
+
+#[test]
+#[cfg(feature = "dalek")]
+fn dalek_conversions() {
+    let public_key = PublicKey::from(ALICE_PUBLIC_KEY);
+    let dalek_public_key = x25519_dalek::PublicKey::from(&public_key);
+    assert_eq!(dalek_public_key.as_bytes(), &ALICE_PUBLIC_KEY);
+    assert_eq!(PublicKey::from(dalek_public_key), public_key);
+
+    let secret_key = SecretKey::from(ALICE_SECRET_KEY);
+    let dalek_secret_key = x25519_dalek::StaticSecret::from(&secret_key);
+    assert_eq!(
+        x25519_dalek::PublicKey::from(&dalek_secret_key).as_bytes(),
+        &ALICE_PUBLIC_KEY
+    );
+    assert_eq!(
+        SecretKey::from(dalek_secret_key).public_key(),
+        secret_key.public_key()
+    );
+}
+
+#[test]
+#[cfg(feature = "dalek")]
+fn dalek_static_secret_diffie_hellman() {
+    let secret_key = SecretKey::from(ALICE_SECRET_KEY);
+    let public_key = PublicKey::from(BOB_PUBLIC_KEY);
+
+    let static_secret = x25519_dalek::StaticSecret::from(&secret_key);
+    let shared_secret = static_secret.diffie_hellman(&x25519_dalek::PublicKey::from(&public_key));
+    assert_eq!(
+        shared_secret.as_bytes(),
+        &secret_key.diffie_hellman(&public_key)
+    );
+
+    let nonce = GenericArray::from_slice(NONCE);
+    let dalek_box = SalsaBox::from_key(GenericArray::from(crypto_box::hazmat::hsalsa_derive(
+        shared_secret.as_bytes(),
+    )));
+    assert_eq!(
+        dalek_box.encrypt(nonce, PLAINTEXT).unwrap(),
+        SalsaBox::new(&public_key, &secret_key)
+            .encrypt(nonce, PLAINTEXT)
+            .unwrap()
+    );
+}
+
+#[test]
+#[cfg(feature = "dalek")]
+fn from_dalek_shared() {
+    let alice_secret = x25519_dalek::StaticSecret::from(ALICE_SECRET_KEY);
+    let bob_public = x25519_dalek::PublicKey::from(BOB_PUBLIC_KEY);
+    let shared_secret = alice_secret.diffie_hellman(&bob_public);
+
+    let nonce = GenericArray::from_slice(NONCE);
+    assert_eq!(
+        SalsaBox::from_dalek_shared(&shared_secret)
+            .encrypt(nonce, PLAINTEXT)
+            .unwrap(),
+        xsalsa20poly1305::CIPHERTEXT
+    );
+}
+
+#[test]
+fn from_entropy() {
+    let mut counter = 0u8;
+    let secret_key = SecretKey::from_entropy(|buf| {
+        for byte in buf.iter_mut() {
+            *byte = counter;
+            counter = counter.wrapping_add(1);
+        }
+    });
+
+    let mut expected = [0u8; 32];
+    for (i, byte) in expected.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    assert_eq!(secret_key.as_bytes(), &expected);
+
+    let secret_key = SecretKey::from_entropy(|buf| buf.copy_from_slice(&ALICE_SECRET_KEY));
+    assert_eq!(secret_key.public_key().as_bytes(), &ALICE_PUBLIC_KEY);
+}
+
+#[test]
+fn generate_clamped() {
+    fn is_clamped(bytes: &[u8; 32]) -> bool {
+        bytes[0] & 7 == 0 && bytes[31] & 0xc0 == 0x40
+    }
+
+    let mut degenerate = 0;
+    for _ in 0..2048 {
+        let secret_key = SecretKey::generate_clamped(&mut rand_core::OsRng, false);
+        assert!(is_clamped(secret_key.as_bytes()));
+        if secret_key.as_bytes()[31] == 0x40 {
+            degenerate += 1;
+        }
+
+        let secret_key = SecretKey::generate_clamped(&mut rand_core::OsRng, true);
+        assert!(is_clamped(secret_key.as_bytes()));
+        assert_ne!(secret_key.as_bytes()[31], 0x40);
+    }
+
+    // Expected about 32 degenerate top bytes without rejection
+    assert!(degenerate < 128);
+}
+
+#[test]
+fn generate_with_nonce() {
+    let (secret_key, nonce) = SecretKey::generate_with_nonce(&mut rand_core::OsRng);
+    assert_ne!(&secret_key.as_bytes()[..24], nonce.as_slice());
+
+    let (other_secret_key, other_nonce) = SecretKey::generate_with_nonce(&mut rand_core::OsRng);
+    assert_ne!(secret_key.as_bytes(), other_secret_key.as_bytes());
+    assert_ne!(nonce, other_nonce);
+}
+
+#[test]
+fn zeroize_secret_key() {
+    use zeroize::Zeroize;
+
+    let mut secret_key = SecretKey::from(ALICE_SECRET_KEY);
+    secret_key.zeroize();
+    assert_eq!(secret_key.as_bytes(), &[0u8; 32]);
+}
+
+#[test]
+fn zeroize_public_key() {
+    use zeroize::Zeroize;
+
+    let mut public_key = PublicKey::from(ALICE_PUBLIC_KEY);
+    public_key.zeroize();
+    assert_eq!(public_key.as_bytes(), &[0u8; 32]);
+}
+
+mod builder {
+    use super::*;
+    use crypto_box::{BoxBuilder, Nonce, NonceCounter, NonceStrategy};
+    use std::collections::HashSet;
+
+    #[test]
+    fn random_strategy() {
+        let mut rng = rand_core::OsRng;
+        let mut nonced_box = BoxBuilder::new(alice_box())
+            .nonce_strategy(NonceStrategy::Random(&mut rng))
+            .build();
+
+        let mut nonces = HashSet::new();
+        for _ in 0..16 {
+            let (nonce, ciphertext) = nonced_box.encrypt(b"", PLAINTEXT).unwrap();
+            assert_eq!(
+                bob_box().decrypt(&nonce, &ciphertext[..]).unwrap(),
+                PLAINTEXT
+            );
+            assert!(nonces.insert(nonce));
+        }
+    }
+
+    #[test]
+    fn counter_strategy() {
+        let mut nonced_box = BoxBuilder::new(alice_box())
+            .nonce_strategy(NonceStrategy::Counter(*GenericArray::from_slice(NONCE)))
+            .build();
+        let mut counter = NonceCounter::new(*GenericArray::from_slice(NONCE));
+
+        let mut nonces = HashSet::new();
+        for _ in 0..16 {
+            let (nonce, ciphertext) = nonced_box.encrypt(b"", PLAINTEXT).unwrap();
+            assert_eq!(nonce, counter.next_nonce().unwrap());
+            assert_eq!(
+                bob_box().decrypt(&nonce, &ciphertext[..]).unwrap(),
+                PLAINTEXT
+            );
+            assert!(nonces.insert(nonce));
+        }
+    }
+
+    #[test]
+    fn encrypt_next() {
+        let mut nonced_box = BoxBuilder::new(alice_box())
+            .nonce_strategy(NonceStrategy::Counter(*GenericArray::from_slice(NONCE)))
+            .build();
+
+        let mut previous: Option<Nonce> = None;
+        for _ in 0..16 {
+            let (nonce, ciphertext) = nonced_box.encrypt_next(PLAINTEXT).unwrap();
+            assert_eq!(
+                bob_box().decrypt(&nonce, &ciphertext[..]).unwrap(),
+                PLAINTEXT
+            );
+
+            // Nonces are increasing little-endian integers
+            if let Some(previous) = previous {
+                assert!(nonce.iter().rev().gt(previous.iter().rev()));
+            }
+            previous = Some(nonce);
+        }
+    }
+
+    #[test]
+    fn counter_exhaustion() {
+        let mut counter = NonceCounter::new(*GenericArray::from_slice(&[0xff; 24]));
+        assert!(counter.next_nonce().is_ok());
+        assert!(counter.next_nonce().is_err());
+    }
+
+    #[test]
+    fn external_strategy() {
+        let mut nonced_box = BoxBuilder::new(alice_box()).build();
+        let nonce = *GenericArray::from_slice(NONCE);
+
+        nonced_box.set_nonce(nonce).unwrap();
+        let (used_nonce, ciphertext) = nonced_box.encrypt(b"", PLAINTEXT).unwrap();
+        assert_eq!(used_nonce, nonce);
+        assert_eq!(xsalsa20poly1305::CIPHERTEXT, &ciphertext[..]);
+
+        // Each supplied nonce can only be used once
+        assert!(nonced_box.encrypt(b"", PLAINTEXT).is_err());
+    }
+}
+
+mod pad {
+    use crypto_box::pad::{pad_message, unpad_message};
+
+    #[test]
+    fn round_trip() {
+        for len in 0..40 {
+            let message = vec![0x80; len];
+            let padded = pad_message(&message, 16);
+            assert_eq!(padded.len() % 16, 0);
+            assert!(padded.len() > len);
+            assert_eq!(unpad_message(&padded, 16).unwrap(), &message[..]);
+        }
+    }
+
+    #[test]
+    fn multiple_of_block_size() {
+        let padded = pad_message(&[1; 16], 16);
+        assert_eq!(padded.len(), 32);
+        assert_eq!(padded[16], 0x80);
+        assert_eq!(&padded[17..], &[0; 15]);
+        assert_eq!(unpad_message(&padded, 16).unwrap(), &[1; 16]);
+    }
+
+    #[test]
+    fn invalid_padding() {
+        assert!(unpad_message(&[0; 16], 16).is_err());
+        assert!(unpad_message(&[0x80; 8], 16).is_err());
+        assert!(unpad_message(&[0x80; 16], 0).is_err());
+
+        let mut padded = pad_message(b"message", 16);
+        *padded.last_mut().unwrap() = 1;
+        assert!(unpad_message(&padded, 16).is_err());
+    }
+}
+
+#[test]
+fn public_key_from_hex() {
+    use crypto_box::Error;
+
+    let hex = "ac3a70ba35df3c3fae427a7c72021d68f2c1e044040b75f17313c0c8b5d4241d";
+    assert_eq!(
+        PublicKey::from_hex(hex).unwrap().as_bytes(),
+        &ALICE_PUBLIC_KEY
+    );
+    assert_eq!(
+        PublicKey::from_hex(&hex.to_uppercase()).unwrap().as_bytes(),
+        &ALICE_PUBLIC_KEY
+    );
+
+    assert_eq!(PublicKey::from_hex(&hex[1..]), Err(Error::OddLength));
+    assert_eq!(
+        PublicKey::from_hex(&hex[2..]),
+        Err(Error::InvalidLength {
+            expected: 64,
+            got: 62
+        })
+    );
+    assert_eq!(
+        PublicKey::from_hex(&hex.replace('a', "g")),
+        Err(Error::InvalidHexCharacter)
+    );
+}
+
+#[test]
+#[cfg(feature = "blake2")]
+fn key_confirmation() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+    let other_box = SalsaBox::new(
+        &PublicKey::from(BOB_PUBLIC_KEY),
+        &SecretKey::generate(&mut rand_core::OsRng),
+    );
+
+    assert_eq!(
+        alice_box.key_confirmation(b"transcript"),
+        bob_box.key_confirmation(b"transcript")
+    );
+    assert_ne!(
+        alice_box.key_confirmation(b"transcript"),
+        other_box.key_confirmation(b"transcript")
+    );
+}
+
+#[test]
+#[cfg(feature = "hkdf")]
+fn rekey() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let ciphertext = alice_box.rekey(1).encrypt(nonce, PLAINTEXT).unwrap();
+    assert_eq!(
+        bob_box.rekey(1).decrypt(nonce, &ciphertext[..]).unwrap(),
+        PLAINTEXT
+    );
+    assert!(bob_box.rekey(2).decrypt(nonce, &ciphertext[..]).is_err());
+    assert!(bob_box.decrypt(nonce, &ciphertext[..]).is_err());
+
+    let ciphertext = alice_box.rekey(2).encrypt(nonce, PLAINTEXT).unwrap();
+    assert!(bob_box.rekey(1).decrypt(nonce, &ciphertext[..]).is_err());
+}
+
+#[test]
+fn new_aead() {
+    use crypto_box::{aead::NewAead, hazmat};
+
+    fn encrypt_generic<A: Aead + NewAead>(key: &[u8; 32]) -> Vec<u8> {
+        A::new(GenericArray::from_slice(key))
+            .encrypt(GenericArray::from_slice(NONCE), PLAINTEXT)
+            .unwrap()
+    }
+
+    let secret_key = SecretKey::from(ALICE_SECRET_KEY);
+    let public_key = PublicKey::from(BOB_PUBLIC_KEY);
+    let shared_secret = secret_key.diffie_hellman(&public_key);
+
+    assert_eq!(
+        encrypt_generic::<SalsaBox>(&hazmat::hsalsa_derive(&shared_secret)),
+        xsalsa20poly1305::CIPHERTEXT
+    );
+    assert_eq!(
+        encrypt_generic::<ChaChaBox>(&hazmat::hchacha_derive(&shared_secret)),
+        xchacha20poly1305::CIPHERTEXT
+    );
+}
+
+#[test]
+fn tag_prefixed() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let ciphertext = alice_box.encrypt_tag_prefixed(nonce, PLAINTEXT).unwrap();
+    assert_eq!(ciphertext, xsalsa20poly1305::CIPHERTEXT);
+    assert_eq!(
+        bob_box.decrypt_tag_prefixed(nonce, &ciphertext).unwrap(),
+        PLAINTEXT
+    );
+
+    let mut tampered = ciphertext;
+    tampered[0] ^= 1;
+    assert!(bob_box.decrypt_tag_prefixed(nonce, &tampered).is_err());
+    assert!(bob_box
+        .decrypt_tag_prefixed(nonce, &tampered[..15])
+        .is_err());
+}
+
+#[test]
+#[cfg(feature = "blake2")]
+fn channel_id() {
+    let alice_pk = PublicKey::from(ALICE_PUBLIC_KEY);
+    let bob_pk = PublicKey::from(BOB_PUBLIC_KEY);
+    let carol_pk = SecretKey::generate(&mut rand_core::OsRng).public_key();
+
+    assert_eq!(
+        crypto_box::channel_id(&alice_pk, &bob_pk),
+        crypto_box::channel_id(&bob_pk, &alice_pk)
+    );
+    assert_ne!(
+        crypto_box::channel_id(&alice_pk, &bob_pk),
+        crypto_box::channel_id(&alice_pk, &carol_pk)
+    );
+}
+
+#[test]
+fn aad_chunks() {
+    let chacha_box = ChaChaBox::new(
+        &PublicKey::from(BOB_PUBLIC_KEY),
+        &SecretKey::from(ALICE_SECRET_KEY),
+    );
+    let nonce = GenericArray::from_slice(NONCE);
+    let aad: &[u8] = b"header one|header two which is longer than a block|three";
+    let chunks: &[&[u8]] = &[&aad[..11], &aad[11..11], &aad[11..50], &aad[50..]];
+
+    let mut expected = PLAINTEXT.to_vec();
+    let expected_tag = chacha_box
+        .encrypt_in_place_detached(nonce, aad, &mut expected)
+        .unwrap();
+
+    let mut buffer = PLAINTEXT.to_vec();
+    let tag = chacha_box
+        .encrypt_in_place_detached_with_aad_chunks(nonce, chunks, &mut buffer)
+        .unwrap();
+    assert_eq!(tag, expected_tag);
+    assert_eq!(buffer, expected);
+
+    chacha_box
+        .decrypt_in_place_detached_with_aad_chunks(nonce, chunks, &mut buffer, &tag)
+        .unwrap();
+    assert_eq!(buffer, PLAINTEXT);
+
+    let mut buffer = expected.clone();
+    assert!(chacha_box
+        .decrypt_in_place_detached_with_aad_chunks(nonce, &chunks[1..], &mut buffer, &tag)
+        .is_err());
+    assert_eq!(buffer, expected);
+}
+
+#[test]
+fn public_key_ord() {
+    use std::collections::BTreeSet;
+
+    let mut public_keys = BTreeSet::new();
+    public_keys.insert(PublicKey::from(BOB_PUBLIC_KEY));
+    public_keys.insert(PublicKey::from(ALICE_PUBLIC_KEY));
+    public_keys.insert(PublicKey::from([0; 32]));
+    public_keys.insert(PublicKey::from(ALICE_PUBLIC_KEY));
+
+    assert_eq!(public_keys.len(), 3);
+    assert!(public_keys.contains(&PublicKey::from(ALICE_PUBLIC_KEY)));
+    assert!(!public_keys.contains(&PublicKey::from([1; 32])));
+    assert_eq!(
+        public_keys.iter().next().unwrap(),
+        &PublicKey::from([0; 32])
+    );
+}
+
+#[test]
+fn from_borrowed_array() {
+    use std::convert::TryInto;
+
+    let mut buffer = [0u8; 64];
+    buffer[..32].copy_from_slice(&ALICE_SECRET_KEY);
+    buffer[32..].copy_from_slice(&BOB_PUBLIC_KEY);
+
+    let (secret_key_bytes, public_key_bytes) = buffer.split_at(32);
+    let secret_key_bytes: &[u8; 32] = secret_key_bytes.try_into().unwrap();
+    let public_key_bytes: &[u8; 32] = public_key_bytes.try_into().unwrap();
+
+    assert_eq!(
+        SecretKey::from(secret_key_bytes).as_bytes(),
+        &ALICE_SECRET_KEY
+    );
+    assert_eq!(
+        PublicKey::from(public_key_bytes),
+        PublicKey::from(BOB_PUBLIC_KEY)
+    );
+}
+
+#[test]
+fn generate_nonce_array() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+
+    let nonce_bytes: [u8; 24] = crypto_box::generate_nonce_array(&mut rand_core::OsRng);
+    let nonce = crypto_box::Nonce::from(nonce_bytes);
+    let ciphertext = alice_box.encrypt(&nonce, PLAINTEXT).unwrap();
+
+    let nonce = crypto_box::Nonce::from(nonce_bytes);
+    assert_eq!(bob_box.decrypt(&nonce, &ciphertext[..]).unwrap(), PLAINTEXT);
+}
+
+#[test]
+fn encrypt_checked_nonce() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+    let zero_nonce = crypto_box::Nonce::default();
+
+    assert_eq!(
+        alice_box.encrypt_checked_nonce(&zero_nonce, PLAINTEXT, false),
+        Err(crypto_box::Error::ZeroNonce)
+    );
+
+    let ciphertext = alice_box
+        .encrypt_checked_nonce(&zero_nonce, PLAINTEXT, true)
+        .unwrap();
+    assert_eq!(
+        bob_box.decrypt(&zero_nonce, &ciphertext[..]).unwrap(),
+        PLAINTEXT
+    );
+
+    let nonce = GenericArray::from_slice(NONCE);
+    assert_eq!(
+        alice_box
+            .encrypt_checked_nonce(nonce, PLAINTEXT, false)
+            .unwrap(),
+        xsalsa20poly1305::CIPHERTEXT
+    );
+}
+
+#[test]
+#[cfg(feature = "heapless")]
+fn encrypt_heapless() {
+    let alice_box = alice_box();
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let ciphertext = alice_box.encrypt_heapless::<147>(nonce, PLAINTEXT).unwrap();
+    assert_eq!(&ciphertext[..], xsalsa20poly1305::CIPHERTEXT);
+
+    assert_eq!(
+        alice_box.encrypt_heapless::<146>(nonce, PLAINTEXT),
+        Err(crypto_box::Error::BufferTooSmall {
+            required: 147,
+            capacity: 146
+        })
+    );
+}
+
+#[test]
+fn cross_cipher() {
+    let alice_sk = SecretKey::from(ALICE_SECRET_KEY);
+    let bob_sk = SecretKey::from(BOB_SECRET_KEY);
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let salsa_ciphertext = SalsaBox::new(&bob_sk.public_key(), &alice_sk)
+        .encrypt(nonce, PLAINTEXT)
+        .unwrap();
+    let chacha_ciphertext = ChaChaBox::new(&bob_sk.public_key(), &alice_sk)
+        .encrypt(nonce, PLAINTEXT)
+        .unwrap();
+
+    assert!(ChaChaBox::new(&alice_sk.public_key(), &bob_sk)
+        .decrypt(nonce, &salsa_ciphertext[..])
+        .is_err());
+    assert!(SalsaBox::new(&alice_sk.public_key(), &bob_sk)
+        .decrypt(nonce, &chacha_ciphertext[..])
+        .is_err());
+}
+
+#[test]
+fn encrypted_message() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+
+    let mut message = alice_box
+        .encrypt_message(&mut rand_core::OsRng, PLAINTEXT)
+        .unwrap();
+    assert_eq!(bob_box.decrypt_message(&message).unwrap(), PLAINTEXT);
+
+    message.nonce[0] ^= 1;
+    assert!(bob_box.decrypt_message(&message).is_err());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn encrypted_message_serialization() {
+    use crypto_box::EncryptedMessage;
+
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+
+    let message = alice_box
+        .encrypt_message(&mut rand_core::OsRng, PLAINTEXT)
+        .unwrap();
+
+    let serialized = bincode::serialize(&message).unwrap();
+    let deserialized: EncryptedMessage = bincode::deserialize(&serialized).unwrap();
+    assert_eq!(deserialized, message);
+    assert_eq!(bob_box.decrypt_message(&deserialized).unwrap(), PLAINTEXT);
+
+    let serialized = rmp_serde::to_vec(&message).unwrap();
+    let deserialized: EncryptedMessage = rmp_serde::from_slice(&serialized).unwrap();
+    assert_eq!(deserialized, message);
+
+    let truncated = bincode::serialize(&&message.nonce[..23]).unwrap();
+    assert!(bincode::deserialize::<EncryptedMessage>(&truncated).is_err());
+
+    // A huge sequence length with a short body fails cleanly, rather than
+    // allocating for the claimed length up front
+    let huge = [0xdd, 0xff, 0xff, 0xff, 0xff, 0x00];
+    assert!(rmp_serde::from_slice::<EncryptedMessage>(&huge).is_err());
+}
+
+#[test]
+#[cfg(feature = "hazmat-reduced")]
+fn hazmat_reduced() {
+    use crypto_box::hazmat::{self, reduced};
+
+    let secret_key = SecretKey::from(ALICE_SECRET_KEY);
+    let public_key = PublicKey::from(BOB_PUBLIC_KEY);
+    let shared_secret = secret_key.diffie_hellman(&public_key);
+
+    // Full-round derivation matches the standard construction
+    assert_eq!(
+        reduced::hsalsa_derive(20, &shared_secret),
+        hazmat::hsalsa_derive(&shared_secret)
+    );
+    assert_eq!(
+        reduced::hchacha_derive(20, &shared_secret),
+        hazmat::hchacha_derive(&shared_secret)
+    );
+
+    for rounds in &[0, 1, 7, 8, 12] {
+        assert_ne!(
+            reduced::hsalsa_derive(*rounds, &shared_secret),
+            hazmat::hsalsa_derive(&shared_secret)
+        );
+        assert_ne!(
+            reduced::hchacha_derive(*rounds, &shared_secret),
+            hazmat::hchacha_derive(&shared_secret)
+        );
+    }
+
+    let nonce = GenericArray::from_slice(NONCE);
+    let ciphertext = reduced::salsa_box(8, &public_key, &secret_key)
+        .encrypt(nonce, PLAINTEXT)
+        .unwrap();
+    assert_ne!(ciphertext, xsalsa20poly1305::CIPHERTEXT);
+    assert_eq!(
+        reduced::salsa_box(20, &public_key, &secret_key)
+            .encrypt(nonce, PLAINTEXT)
+            .unwrap(),
+        xsalsa20poly1305::CIPHERTEXT
+    );
+    assert_eq!(
+        reduced::chacha_box(20, &public_key, &secret_key)
+            .encrypt(nonce, PLAINTEXT)
+            .unwrap(),
+        xchacha20poly1305::CIPHERTEXT
+    );
+}
+
+#[test]
+fn try_decrypt() {
+    let bob_box = bob_box();
+    let nonce = GenericArray::from_slice(NONCE);
+
+    assert_eq!(
+        bob_box.try_decrypt(nonce, xsalsa20poly1305::CIPHERTEXT),
+        Ok(PLAINTEXT.to_vec())
+    );
+    assert_eq!(
+        bob_box.try_decrypt(nonce, &xsalsa20poly1305::CIPHERTEXT[..5]),
+        Err(crypto_box::Error::CiphertextTooShort { min: 16, got: 5 })
+    );
+
+    let mut tampered = xsalsa20poly1305::CIPHERTEXT.to_vec();
+    tampered[20] ^= 1;
+    assert_eq!(
+        bob_box.try_decrypt(nonce, &tampered),
+        Err(crypto_box::Error::Aead)
+    );
+}
+
+#[test]
+fn keypair_from_secret() {
+    use crypto_box::KeyPair;
+
+    let keypair = KeyPair::from_secret(SecretKey::from(ALICE_SECRET_KEY));
+    assert_eq!(keypair.public().as_bytes(), &ALICE_PUBLIC_KEY);
+    assert_eq!(keypair.secret().as_bytes(), &ALICE_SECRET_KEY);
+
+    let secret_key = SecretKey::generate(&mut rand_core::OsRng);
+    let keypair = KeyPair::from(secret_key.clone());
+    assert_eq!(keypair.public(), &secret_key.public_key());
+
+    let (public_key, secret_key) = KeyPair::generate(&mut rand_core::OsRng).split();
+    assert_eq!(public_key, secret_key.public_key());
+}
+
+#[test]
+fn keypair_bytes() {
+    use crypto_box::KeyPair;
+
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(&ALICE_SECRET_KEY);
+    bytes[32..].copy_from_slice(&ALICE_PUBLIC_KEY);
+
+    let keypair = KeyPair::from_bytes(&bytes).unwrap();
+    assert_eq!(keypair.secret().as_bytes(), &ALICE_SECRET_KEY);
+    assert_eq!(keypair.public().as_bytes(), &ALICE_PUBLIC_KEY);
+    assert_eq!(*keypair.to_bytes(), bytes);
+
+    let keypair = KeyPair::generate(&mut rand_core::OsRng);
+    let round_tripped = KeyPair::from_bytes(&keypair.to_bytes()).unwrap();
+    assert_eq!(round_tripped.public(), keypair.public());
+    assert_eq!(
+        round_tripped.secret().as_bytes(),
+        keypair.secret().as_bytes()
+    );
+
+    // Mismatched halves, or the wrong order
+    let mut mismatched = bytes;
+    mismatched[32..].copy_from_slice(&BOB_PUBLIC_KEY);
+    let mut swapped = [0u8; 64];
+    swapped[..32].copy_from_slice(&ALICE_PUBLIC_KEY);
+    swapped[32..].copy_from_slice(&ALICE_SECRET_KEY);
+    for invalid in &[mismatched, swapped] {
+        assert_eq!(
+            KeyPair::from_bytes(invalid).unwrap_err(),
+            crypto_box::Error::PublicKeyMismatch
+        );
+    }
+}
+
+#[test]
+fn precomputed_key_conditional_select() {
+    use crypto_box::PrecomputedKey;
+    use subtle::Choice;
+
+    let secret_key = SecretKey::from(BOB_SECRET_KEY);
+    let other_public_key = SecretKey::generate(&mut rand_core::OsRng).public_key();
+    let a = PrecomputedKey::new(&PublicKey::from(ALICE_PUBLIC_KEY), &secret_key);
+    let b = PrecomputedKey::new(&other_public_key, &secret_key);
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let selected = PrecomputedKey::conditional_select(&a, &b, Choice::from(0));
+    assert_eq!(selected.as_bytes(), a.as_bytes());
+    assert_eq!(
+        SalsaBox::from(&selected)
+            .decrypt(nonce, xsalsa20poly1305::CIPHERTEXT)
+            .unwrap(),
+        PLAINTEXT
+    );
+
+    let ciphertext = SalsaBox::from(&b).encrypt(nonce, PLAINTEXT).unwrap();
+    let selected = PrecomputedKey::conditional_select(&a, &b, Choice::from(1));
+    assert_eq!(selected.as_bytes(), b.as_bytes());
+    assert_eq!(
+        SalsaBox::from(&selected)
+            .decrypt(nonce, &ciphertext[..])
+            .unwrap(),
+        PLAINTEXT
+    );
+    assert!(SalsaBox::from(&selected)
+        .decrypt(nonce, xsalsa20poly1305::CIPHERTEXT)
+        .is_err());
+}
+
+#[cfg(feature = "z85")]
+mod z85 {
+    use super::*;
+
+    // CurveZMQ client keypair from libzmq's test suite
+    const PUBLIC_KEY_Z85: &str = "Yne@$w-vo<fVvi]a<NY6T1ed:M$fCG*[IaLV{hID";
+    const SECRET_KEY_Z85: &str = "D:)Q[IlAW!ahhC2ac:9*A}h:p?([4%wOTJ%JR%cs";
+    const PUBLIC_KEY: [u8; 32] = [
+        0xbb, 0x88, 0x47, 0x1d, 0x65, 0xe2, 0x65, 0x9b, 0x30, 0xc5, 0x5a, 0x53, 0x21, 0xce, 0xbb,
+        0x5a, 0xab, 0x2b, 0x70, 0xa3, 0x98, 0x64, 0x5c, 0x26, 0xdc, 0xa2, 0xb2, 0xfc, 0xb4, 0x3f,
+        0xc5, 0x18,
+    ];
+
+    #[test]
+    fn known_vector() {
+        let public_key = PublicKey::from_z85(PUBLIC_KEY_Z85).unwrap();
+        assert_eq!(public_key.as_bytes(), &PUBLIC_KEY);
+        assert_eq!(public_key.to_z85(), PUBLIC_KEY_Z85);
+
+        let secret_key = SecretKey::from_z85(SECRET_KEY_Z85).unwrap();
+        assert_eq!(secret_key.public_key(), public_key);
+        assert_eq!(secret_key.to_z85().as_str(), SECRET_KEY_Z85);
+    }
+
+    #[test]
+    fn round_trip() {
+        let secret_key = SecretKey::generate(&mut rand_core::OsRng);
+        let public_key = secret_key.public_key();
+
+        assert_eq!(
+            PublicKey::from_z85(&public_key.to_z85()).unwrap(),
+            public_key
+        );
+        assert_eq!(
+            SecretKey::from_z85(&secret_key.to_z85())
+                .unwrap()
+                .as_bytes(),
+            secret_key.as_bytes()
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            PublicKey::from_z85(&PUBLIC_KEY_Z85[..35]),
+            Err(crypto_box::Error::InvalidLength {
+                expected: 40,
+                got: 35
+            })
+        );
+        assert_eq!(
+            PublicKey::from_z85("Yne@$w-vo<fVvi]a<NY6T1ed:M$fCG*[IaLV{hI~"),
+            Err(crypto_box::Error::InvalidZ85)
+        );
+        assert_eq!(
+            PublicKey::from_z85("#####w-vo<fVvi]a<NY6T1ed:M$fCG*[IaLV{hID"),
+            Err(crypto_box::Error::InvalidZ85)
+        );
+    }
+}
+
+#[test]
+fn rederive() {
+    let bob_sk = SecretKey::from(BOB_SECRET_KEY);
+    let carol_sk = SecretKey::generate(&mut rand_core::OsRng);
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let carol_ciphertext = SalsaBox::new(&bob_sk.public_key(), &carol_sk)
+        .encrypt(nonce, PLAINTEXT)
+        .unwrap();
+
+    let mut bob_box = SalsaBox::new(&PublicKey::from(ALICE_PUBLIC_KEY), &bob_sk);
+    assert!(bob_box.decrypt(nonce, xsalsa20poly1305::CIPHERTEXT).is_ok());
+
+    bob_box.rederive(&carol_sk.public_key(), &bob_sk);
+    assert_eq!(
+        bob_box.decrypt(nonce, &carol_ciphertext[..]).unwrap(),
+        PLAINTEXT
+    );
+    assert!(bob_box
+        .decrypt(nonce, xsalsa20poly1305::CIPHERTEXT)
+        .is_err());
+}
+
+#[test]
+fn encrypt_chunks() {
+    use crypto_box::NonceCounter;
+
+    let alice_box = ChaChaBox::new(
+        &PublicKey::from(BOB_PUBLIC_KEY),
+        &SecretKey::from(ALICE_SECRET_KEY),
+    );
+    let bob_box = ChaChaBox::new(
+        &PublicKey::from(ALICE_PUBLIC_KEY),
+        &SecretKey::from(BOB_SECRET_KEY),
+    );
+    let base_nonce = alice_box.generate_nonce(&mut rand_core::OsRng);
+    let chunks = vec![b"first".to_vec(), b"second".to_vec(), b"first".to_vec()];
+
+    let (ciphertexts, count) = alice_box
+        .encrypt_chunks(&base_nonce, b"header", &chunks)
+        .unwrap();
+    assert_eq!(count, 3);
+    assert_eq!(ciphertexts.len(), 3);
+
+    // Identical chunks encrypt differently under distinct nonces
+    assert_ne!(ciphertexts[0], ciphertexts[2]);
+
+    let mut nonces = NonceCounter::new(base_nonce);
+    let mut seen = Vec::new();
+    for (chunk, ciphertext) in chunks.iter().zip(&ciphertexts) {
+        let nonce = nonces.next_nonce().unwrap();
+        assert!(!seen.contains(&nonce));
+        seen.push(nonce);
+
+        let payload = Payload {
+            msg: ciphertext,
+            aad: b"header",
+        };
+        assert_eq!(&bob_box.decrypt(&nonce, payload).unwrap(), chunk);
+    }
+}
+
+#[test]
+fn public_key_is_valid_ct() {
+    let small_order_point = [
+        0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f, 0xc4,
+        0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16, 0x5f, 0x49,
+        0xb8, 0x00,
+    ];
+    let other_small_order_point = [
+        0x5f, 0x9c, 0x95, 0xbc, 0xa3, 0x50, 0x8c, 0x24, 0xb1, 0xd0, 0xb1, 0x55, 0x9c, 0x83, 0xef,
+        0x5b, 0x04, 0x44, 0x5c, 0xc4, 0x58, 0x1c, 0x8e, 0x86, 0xd8, 0x22, 0x4e, 0xdd, 0xd0, 0x9f,
+        0x11, 0x57,
+    ];
+    let mut p_minus_one = [0xff; 32];
+    p_minus_one[0] = 0xec;
+    p_minus_one[31] = 0x7f;
+    let mut one_with_top_bit = [0; 32];
+    one_with_top_bit[0] = 1;
+    one_with_top_bit[31] = 0x80;
+
+    let secret_key = SecretKey::generate(&mut rand_core::OsRng);
+    for bytes in &[
+        [0; 32],
+        small_order_point,
+        other_small_order_point,
+        p_minus_one,
+        one_with_top_bit,
+    ] {
+        let public_key = PublicKey::from(*bytes);
+        assert!(!bool::from(public_key.is_valid_ct()));
+
+        // Blacklisted points result in an all-zero shared secret
+        assert_eq!(secret_key.diffie_hellman(&public_key), [0; 32]);
+    }
+
+    assert!(bool::from(PublicKey::from(ALICE_PUBLIC_KEY).is_valid_ct()));
+    assert!(bool::from(secret_key.public_key().is_valid_ct()));
+}
+
+#[test]
+fn new_clamped() {
+    let public_key = SecretKey::generate(&mut rand_core::OsRng).public_key();
+    let nonce = GenericArray::from_slice(&[7; 24]);
+
+    // A freshly generated key, and a hand-crafted scalar with none of the
+    // clamping bits set: X25519 clamps internally, so neither diverges.
+    for secret_key in &[
+        SecretKey::generate(&mut rand_core::OsRng),
+        SecretKey::from([0x87; 32]),
+    ] {
+        let ciphertext = SalsaBox::new(&public_key, secret_key)
+            .encrypt(nonce, &b"clamped"[..])
+            .unwrap();
+        assert_eq!(
+            SalsaBox::new_clamped(&public_key, secret_key)
+                .encrypt(nonce, &b"clamped"[..])
+                .unwrap(),
+            ciphertext
+        );
+    }
+}
+
+#[test]
+fn decrypt_adversarial_lengths() {
+    let salsa_box = SalsaBox::new(
+        &SecretKey::from(BOB_SECRET_KEY).public_key(),
+        &SecretKey::from(ALICE_SECRET_KEY),
+    );
+    let chacha_box = ChaChaBox::new(
+        &SecretKey::from(BOB_SECRET_KEY).public_key(),
+        &SecretKey::from(ALICE_SECRET_KEY),
+    );
+    let nonce = GenericArray::from_slice(NONCE);
+    let input = vec![0xa5; 1 << 20];
+
+    for len in &[0, 1, 15, 16, 17, 31, 32, 4096, 1 << 20] {
+        let ciphertext = &input[..*len];
+        assert!(salsa_box.decrypt(nonce, ciphertext).is_err());
+        assert!(chacha_box.decrypt(nonce, ciphertext).is_err());
+        assert!(salsa_box.try_decrypt(nonce, ciphertext).is_err());
+    }
+}
+
+#[test]
+fn encrypt_typed() {
+    let alice_sk = SecretKey::from(ALICE_SECRET_KEY);
+    let bob_sk = SecretKey::from(BOB_SECRET_KEY);
+    let alice_box = ChaChaBox::new(&bob_sk.public_key(), &alice_sk);
+    let bob_box = ChaChaBox::new(&alice_sk.public_key(), &bob_sk);
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let ciphertext = alice_box
+        .encrypt_typed(nonce, "command", PLAINTEXT)
+        .unwrap();
+    assert_eq!(
+        bob_box
+            .decrypt_typed(nonce, "command", &ciphertext)
+            .unwrap(),
+        PLAINTEXT
+    );
+
+    // The type is length-prefixed associated data
+    let mut aad = 7u64.to_le_bytes().to_vec();
+    aad.extend_from_slice(b"command");
+    let payload = Payload {
+        msg: PLAINTEXT,
+        aad: &aad,
+    };
+    assert_eq!(alice_box.encrypt(nonce, payload).unwrap(), ciphertext);
+
+    assert!(bob_box.decrypt_typed(nonce, "data", &ciphertext).is_err());
+    assert!(bob_box.decrypt_typed(nonce, "comman", &ciphertext).is_err());
+    assert!(bob_box
+        .decrypt_typed(nonce, "command", &ciphertext[..15])
+        .is_err());
+    assert!(bob_box.decrypt(nonce, &ciphertext[..]).is_err());
+}
+
+#[cfg(feature = "rand_chacha")]
+#[test]
+fn from_rng_seed() {
+    let seed = [0x5e; 32];
+    let secret_key = SecretKey::from_rng_seed(seed);
+
+    assert_eq!(
+        secret_key.as_bytes(),
+        SecretKey::from_rng_seed(seed).as_bytes()
+    );
+    assert_ne!(
+        secret_key.as_bytes(),
+        SecretKey::from_rng_seed([0x5f; 32]).as_bytes()
+    );
+
+    // Pinned, so that any change to the key drawn from a given seed is caught
+    assert_eq!(
+        secret_key.as_bytes(),
+        &[
+            61, 35, 124, 211, 113, 81, 74, 235, 58, 13, 250, 198, 149, 155, 247, 187, 116, 223,
+            121, 134, 182, 3, 126, 83, 76, 46, 149, 131, 55, 105, 2, 156
+        ]
+    );
+}
+
+#[test]
+fn from_dh_output() {
+    let alice_sk = SecretKey::from(ALICE_SECRET_KEY);
+    let bob_pk = PublicKey::from(BOB_PUBLIC_KEY);
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let shared = alice_sk.diffie_hellman(&bob_pk);
+    let ciphertext = SalsaBox::from_dh_output(&shared)
+        .encrypt(nonce, PLAINTEXT)
+        .unwrap();
+    assert_eq!(xsalsa20poly1305::CIPHERTEXT, &ciphertext[..]);
+    assert_eq!(
+        SalsaBox::new(&bob_pk, &alice_sk)
+            .encrypt(nonce, PLAINTEXT)
+            .unwrap(),
+        ciphertext
+    );
+
+    // The DH output is not usable as a key directly
+    assert_ne!(
+        SalsaBox::from_key(GenericArray::from(shared))
+            .encrypt(nonce, PLAINTEXT)
+            .unwrap(),
+        ciphertext
+    );
+}
+
+#[test]
+fn encrypt_binding_nonce() {
+    let alice_sk = SecretKey::from(ALICE_SECRET_KEY);
+    let bob_sk = SecretKey::from(BOB_SECRET_KEY);
+    let alice_box = ChaChaBox::new(&bob_sk.public_key(), &alice_sk);
+    let bob_box = ChaChaBox::new(&alice_sk.public_key(), &bob_sk);
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let ciphertext = alice_box.encrypt_binding_nonce(nonce, PLAINTEXT).unwrap();
+    assert_eq!(
+        bob_box.decrypt_binding_nonce(nonce, &ciphertext).unwrap(),
+        PLAINTEXT
+    );
+
+    for bit in 0..24 * 8 {
+        let mut tampered_nonce = *nonce;
+        tampered_nonce[bit / 8] ^= 1 << (bit % 8);
+        assert!(bob_box
+            .decrypt_binding_nonce(&tampered_nonce, &ciphertext)
+            .is_err());
+    }
+}
+
+#[test]
+fn supported_suites() {
+    use crypto_box::Suite;
+
+    let suites = crypto_box::supported_suites();
+    assert!(suites.contains(&Suite::XSalsa20Poly1305));
+    assert!(suites.contains(&Suite::XChaCha20Poly1305));
+    assert!(suites.contains(&Suite::IetfChaCha20Poly1305));
+    assert_eq!(
+        suites.contains(&Suite::SealedXSalsa20Poly1305),
+        cfg!(feature = "seal")
+    );
+    assert_eq!(suites.len(), if cfg!(feature = "seal") { 4 } else { 3 });
+}
+
+#[test]
+fn export_into() {
+    use zeroize::Zeroize;
+
+    let secret_key = SecretKey::generate(&mut rand_core::OsRng);
+    let mut exported = [0u8; 32];
+    secret_key.export_into(&mut exported);
+
+    let reconstructed = SecretKey::from(exported);
+    assert_eq!(reconstructed.as_bytes(), secret_key.as_bytes());
+    assert_eq!(reconstructed.public_key(), secret_key.public_key());
+
+    exported.zeroize();
+    assert_eq!(exported, [0u8; 32]);
+}
+
+#[test]
+fn scratch_buffer() {
+    use crypto_box::aead::Buffer;
+    use crypto_box::ScratchBuffer;
+
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+    let nonce = GenericArray::from_slice(NONCE);
+    let mut buffer = ScratchBuffer::<256>::new();
+
+    for len in &[PLAINTEXT.len(), 0, 17] {
+        buffer.clear();
+        assert!(buffer.is_empty());
+
+        buffer.extend_from_slice(&PLAINTEXT[..*len]).unwrap();
+        alice_box.encrypt_in_place(nonce, b"", &mut buffer).unwrap();
+        assert_eq!(buffer.len(), len + 16);
+        if *len == PLAINTEXT.len() {
+            assert_eq!(buffer.as_ref(), xsalsa20poly1305::CIPHERTEXT);
+        }
+
+        bob_box.decrypt_in_place(nonce, b"", &mut buffer).unwrap();
+        assert_eq!(buffer.as_ref(), &PLAINTEXT[..*len]);
+    }
+
+    // The message and its tag must fit within the capacity
+    let mut buffer = ScratchBuffer::<32>::new();
+    buffer.extend_from_slice(&PLAINTEXT[..17]).unwrap();
+    assert!(alice_box.encrypt_in_place(nonce, b"", &mut buffer).is_err());
+    buffer.clear();
+    buffer.extend_from_slice(&PLAINTEXT[..16]).unwrap();
+    assert!(alice_box.encrypt_in_place(nonce, b"", &mut buffer).is_ok());
+}
+
+#[cfg(feature = "hkdf")]
+#[test]
+fn derive_enc_mac_keys() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+
+    let (enc_key, mac_key) = alice_box.derive_enc_mac_keys(b"protocol");
+    assert_ne!(enc_key.as_bytes(), mac_key.as_bytes());
+
+    // Deterministic, and the same for both parties
+    let (bob_enc_key, bob_mac_key) = bob_box.derive_enc_mac_keys(b"protocol");
+    assert_eq!(enc_key.as_bytes(), bob_enc_key.as_bytes());
+    assert_eq!(mac_key.as_bytes(), bob_mac_key.as_bytes());
+
+    let (other_enc_key, other_mac_key) = alice_box.derive_enc_mac_keys(b"other protocol");
+    assert_ne!(enc_key.as_bytes(), other_enc_key.as_bytes());
+    assert_ne!(mac_key.as_bytes(), other_mac_key.as_bytes());
+}
+
+#[cfg(feature = "hkdf")]
+#[test]
+fn duplex_keys() {
+    use crypto_box::DuplexKeys;
+
+    let alice_sk = SecretKey::from(ALICE_SECRET_KEY);
+    let bob_sk = SecretKey::from(BOB_SECRET_KEY);
+    let initiator = DuplexKeys::derive(&SalsaBox::new(&bob_sk.public_key(), &alice_sk), true);
+    let responder = DuplexKeys::derive(&SalsaBox::new(&alice_sk.public_key(), &bob_sk), false);
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let ciphertext = initiator.send.encrypt(nonce, PLAINTEXT).unwrap();
+    assert_eq!(
+        responder.recv.decrypt(nonce, &ciphertext[..]).unwrap(),
+        PLAINTEXT
+    );
+    assert!(responder.send.decrypt(nonce, &ciphertext[..]).is_err());
+    // Reflected back to the initiator
+    assert!(initiator.recv.decrypt(nonce, &ciphertext[..]).is_err());
+
+    let ciphertext = responder.send.encrypt(nonce, PLAINTEXT).unwrap();
+    assert_eq!(
+        initiator.recv.decrypt(nonce, &ciphertext[..]).unwrap(),
+        PLAINTEXT
+    );
+    assert!(responder.recv.decrypt(nonce, &ciphertext[..]).is_err());
+}
+
+#[test]
+fn ciphertext_lengths() {
+    assert_eq!(SalsaBox::max_plaintext_len(0), 0);
+    assert_eq!(SalsaBox::max_plaintext_len(15), 0);
+    assert_eq!(SalsaBox::max_plaintext_len(16), 0);
+    assert_eq!(SalsaBox::max_plaintext_len(17), 1);
+
+    assert_eq!(SalsaBox::required_ciphertext_len(0), Some(16));
+    assert_eq!(SalsaBox::required_ciphertext_len(1), Some(17));
+    assert_eq!(
+        SalsaBox::required_ciphertext_len(usize::MAX - 16),
+        Some(usize::MAX)
+    );
+    assert_eq!(SalsaBox::required_ciphertext_len(usize::MAX - 15), None);
+
+    let salsa_box = alice_box();
+    let nonce = GenericArray::from_slice(NONCE);
+    for len in &[0, 1, 16, PLAINTEXT.len()] {
+        let ciphertext = salsa_box.encrypt(nonce, &PLAINTEXT[..*len]).unwrap();
+        assert_eq!(
+            SalsaBox::required_ciphertext_len(*len),
+            Some(ciphertext.len())
+        );
+        assert_eq!(SalsaBox::max_plaintext_len(ciphertext.len()), *len);
+    }
+}
+
+#[test]
+fn encrypt_with_expiry() {
+    let alice_sk = SecretKey::from(ALICE_SECRET_KEY);
+    let bob_sk = SecretKey::from(BOB_SECRET_KEY);
+    let alice_box = ChaChaBox::new(&bob_sk.public_key(), &alice_sk);
+    let bob_box = ChaChaBox::new(&alice_sk.public_key(), &bob_sk);
+    let nonce = GenericArray::from_slice(NONCE);
+    let sent_at = 1_700_000_000;
+
+    let ciphertext = alice_box
+        .encrypt_with_expiry(nonce, sent_at, PLAINTEXT)
+        .unwrap();
+    assert_eq!(&ciphertext[..8], &sent_at.to_le_bytes());
+
+    // Fresh, at the limit, and from the future
+    for now in &[sent_at, sent_at + 30, sent_at - 5] {
+        assert_eq!(
+            bob_box
+                .decrypt_with_expiry(nonce, &ciphertext, *now, 30)
+                .unwrap(),
+            PLAINTEXT
+        );
+    }
+    assert_eq!(
+        bob_box.decrypt_with_expiry(nonce, &ciphertext, sent_at + 31, 30),
+        Err(crypto_box::Error::Expired)
+    );
+
+    // The timestamp is authenticated
+    let mut tampered = ciphertext.clone();
+    tampered[..8].copy_from_slice(&(sent_at + 60).to_le_bytes());
+    assert_eq!(
+        bob_box.decrypt_with_expiry(nonce, &tampered, sent_at + 60, 30),
+        Err(crypto_box::Error::Aead)
+    );
+    assert_eq!(
+        bob_box.decrypt_with_expiry(nonce, &ciphertext[..23], sent_at, 30),
+        Err(crypto_box::Error::Aead)
+    );
+}
+
+#[test]
+fn split() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let (first, second) = bob_box.split();
+    for salsa_box in &[&first, &second] {
+        assert_eq!(
+            salsa_box
+                .decrypt(nonce, xsalsa20poly1305::CIPHERTEXT)
+                .unwrap(),
+            PLAINTEXT
+        );
+    }
+
+    // Dropping (and so zeroizing) one box leaves the other intact
+    drop(first);
+    let ciphertext = alice_box.encrypt(nonce, PLAINTEXT).unwrap();
+    assert_eq!(second.decrypt(nonce, &ciphertext[..]).unwrap(), PLAINTEXT);
+    assert_eq!(
+        second.encrypt(nonce, PLAINTEXT).unwrap(),
+        xsalsa20poly1305::CIPHERTEXT
+    );
+}
+
+#[test]
+fn channel_binding() {
+    let alice_sk = SecretKey::from(ALICE_SECRET_KEY);
+    let bob_sk = SecretKey::from(BOB_SECRET_KEY);
+    let alice_pk = alice_sk.public_key();
+    let bob_pk = bob_sk.public_key();
+    let other_pk = SecretKey::generate(&mut rand_core::OsRng).public_key();
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let alice_box = ChaChaBox::new(&bob_pk, &alice_sk).with_channel_binding(&alice_pk, &bob_pk);
+    let bob_box = ChaChaBox::new(&alice_pk, &bob_sk);
+
+    let ciphertext = alice_box.encrypt(nonce, PLAINTEXT).unwrap();
+    assert_eq!(
+        bob_box
+            .clone()
+            .with_channel_binding(&bob_pk, &alice_pk)
+            .decrypt(nonce, &ciphertext[..])
+            .unwrap(),
+        PLAINTEXT
+    );
+
+    // Both keys, in canonical order, are authenticated before the caller's
+    // associated data
+    let (first, second) = if alice_pk < bob_pk {
+        (&alice_pk, &bob_pk)
+    } else {
+        (&bob_pk, &alice_pk)
+    };
+    let mut aad = first.as_bytes().to_vec();
+    aad.extend_from_slice(second.as_bytes());
+    aad.extend_from_slice(b"header");
+    let payload = Payload {
+        msg: &ciphertext,
+        aad: &aad[..64],
+    };
+    assert_eq!(bob_box.decrypt(nonce, payload).unwrap(), PLAINTEXT);
+    let payload = Payload {
+        msg: PLAINTEXT,
+        aad: b"header",
+    };
+    let with_aad = alice_box.encrypt(nonce, payload).unwrap();
+    let payload = Payload {
+        msg: &with_aad,
+        aad: &aad,
+    };
+    assert_eq!(bob_box.decrypt(nonce, payload).unwrap(), PLAINTEXT);
+
+    // A different binding, or none at all, fails to decrypt
+    for bound_box in &[
+        bob_box.clone().with_channel_binding(&bob_pk, &other_pk),
+        bob_box.clone().with_channel_binding(&other_pk, &alice_pk),
+    ] {
+        assert!(bound_box.decrypt(nonce, &ciphertext[..]).is_err());
+    }
+    assert!(bob_box.decrypt(nonce, &ciphertext[..]).is_err());
+}
+
+#[test]
+fn encrypt_bounded() {
+    let salsa_box = alice_box();
+    let nonce = GenericArray::from_slice(NONCE);
+
+    assert_eq!(
+        salsa_box
+            .encrypt_bounded(nonce, PLAINTEXT, PLAINTEXT.len())
+            .unwrap(),
+        xsalsa20poly1305::CIPHERTEXT
+    );
+    assert_eq!(
+        salsa_box.encrypt_bounded(nonce, PLAINTEXT, PLAINTEXT.len() - 1),
+        Err(crypto_box::Error::TooLarge {
+            max: PLAINTEXT.len() - 1,
+            got: PLAINTEXT.len(),
+        })
+    );
+
+    assert_eq!(salsa_box.encrypt_bounded(nonce, &[], 0).unwrap().len(), 16);
+    assert!(matches!(
+        salsa_box.encrypt_bounded(nonce, &[0; 1], 0),
+        Err(crypto_box::Error::TooLarge { max: 0, got: 1 })
+    ));
+}
+
+#[test]
+fn verify_tag() {
+    use crypto_box::{verify_tag, Tag};
+    use subtle::ConstantTimeEq;
+
+    let tag = *Tag::from_slice(&xsalsa20poly1305::CIPHERTEXT[..16]);
+    let mut tampered = tag;
+    tampered[15] ^= 1;
+
+    for other in &[tag, tampered, Tag::default()] {
+        assert_eq!(verify_tag(&tag, other), bool::from(tag.ct_eq(other)));
+    }
+    assert!(verify_tag(&tag, &tag));
+    assert!(!verify_tag(&tag, &tampered));
+}
+
+#[test]
+fn decrypt_with_keys() {
+    let alice_pk = PublicKey::from(ALICE_PUBLIC_KEY);
+    let nonce = GenericArray::from_slice(NONCE);
+    let mut secret_keys: Vec<_> = (0..4)
+        .map(|_| SecretKey::generate(&mut rand_core::OsRng))
+        .collect();
+
+    assert!(SalsaBox::decrypt_with_keys(
+        &alice_pk,
+        &secret_keys,
+        nonce,
+        xsalsa20poly1305::CIPHERTEXT
+    )
+    .is_none());
+    assert!(
+        SalsaBox::decrypt_with_keys(&alice_pk, &[], nonce, xsalsa20poly1305::CIPHERTEXT).is_none()
+    );
+
+    secret_keys.insert(2, SecretKey::from(BOB_SECRET_KEY));
+    let (index, plaintext) =
+        SalsaBox::decrypt_with_keys(&alice_pk, &secret_keys, nonce, xsalsa20poly1305::CIPHERTEXT)
+            .unwrap();
+    assert_eq!(index, 2);
+    assert_eq!(plaintext, PLAINTEXT);
+}
+
+#[test]
+fn decrypt_records() {
+    use crypto_box::NonceCounter;
+
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+    let records: [&[u8]; 3] = [b"first", b"", PLAINTEXT];
+
+    let mut nonces = NonceCounter::default();
+    let mut data = Vec::new();
+    for record in &records {
+        let ciphertext = alice_box
+            .encrypt(&nonces.next_nonce().unwrap(), *record)
+            .unwrap();
+        data.extend_from_slice(&(ciphertext.len() as u32).to_le_bytes());
+        data.extend_from_slice(&ciphertext);
+    }
+
+    let decrypted: Vec<_> = bob_box
+        .decrypt_records(NonceCounter::default(), &data)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(decrypted, records);
+
+    // Truncated final record
+    let mut decrypted = bob_box.decrypt_records(NonceCounter::default(), &data[..data.len() - 1]);
+    assert_eq!(decrypted.next().unwrap().unwrap(), records[0]);
+    assert_eq!(decrypted.next().unwrap().unwrap(), records[1]);
+    assert_eq!(
+        decrypted.next().unwrap(),
+        Err(crypto_box::Error::InvalidLength {
+            expected: PLAINTEXT.len() + 16,
+            got: PLAINTEXT.len() + 15
+        })
+    );
+    assert!(decrypted.next().is_none());
+
+    // Truncated length prefix
+    let mut decrypted = bob_box.decrypt_records(NonceCounter::default(), &data[..2]);
+    assert_eq!(
+        decrypted.next().unwrap(),
+        Err(crypto_box::Error::InvalidLength {
+            expected: 4,
+            got: 2
+        })
+    );
+    assert!(decrypted.next().is_none());
+
+    // Wrong nonces
+    let mut decrypted =
+        bob_box.decrypt_records(NonceCounter::new(*GenericArray::from_slice(NONCE)), &data);
+    assert_eq!(decrypted.next().unwrap(), Err(crypto_box::Error::Aead));
+    assert!(decrypted.next().is_none());
+}
+
+#[test]
+#[cfg(feature = "blake2")]
+fn encrypt_committing() {
+    let alice_box = ChaChaBox::from_key(GenericArray::from([1; 32]));
+    let mallory_box = ChaChaBox::from_key(GenericArray::from([2; 32]));
+    let nonce = GenericArray::from_slice(NONCE);
+
+    let ciphertext = alice_box.encrypt_committing(nonce, PLAINTEXT).unwrap();
+    assert_eq!(ciphertext.len(), PLAINTEXT.len() + 48);
+
+    // Known answer, pinning the derivation of the committed subkey
+    assert_eq!(
+        ciphertext[..32],
+        [
+            0x09, 0xec, 0x77, 0x26, 0xa0, 0x5a, 0x95, 0xa0, 0x39, 0xed, 0xb7, 0x14, 0xa2, 0xc7,
+            0x72, 0x7e, 0xa6, 0xfe, 0x30, 0xa7, 0x5a, 0x3f, 0xc6, 0x63, 0x69, 0x7b, 0x68, 0x06,
+            0x58, 0xe4, 0x27, 0x02,
+        ]
+    );
+    assert_eq!(
+        alice_box.decrypt_committing(nonce, &ciphertext).unwrap(),
+        PLAINTEXT
+    );
+    assert!(mallory_box.decrypt_committing(nonce, &ciphertext).is_err());
+
+    // A ciphertext crafted to open under a second key must also carry the
+    // commitment to that key, so it can't pass as one for the first key
+    let mut crafted = mallory_box.encrypt_committing(nonce, PLAINTEXT).unwrap();
+    crafted[..32].copy_from_slice(&ciphertext[..32]);
+    assert!(alice_box.decrypt_committing(nonce, &crafted).is_err());
+    assert!(mallory_box.decrypt_committing(nonce, &crafted).is_err());
+
+    // The commitment is bound to the nonce as well
+    let other_nonce = GenericArray::from_slice(&[0; 24]);
+    assert!(alice_box
+        .decrypt_committing(other_nonce, &ciphertext)
+        .is_err());
+    assert!(alice_box
+        .decrypt_committing(nonce, &ciphertext[..47])
+        .is_err());
+}
+
+#[test]
+fn generate_keypair_into() {
+    let mut secret = [0u8; 32];
+    let mut public = [0u8; 32];
+    crypto_box::generate_keypair_into(&mut rand_core::OsRng, &mut secret, &mut public);
+
+    let secret_key = SecretKey::from(secret);
+    assert_eq!(secret_key.public_key().as_bytes(), &public);
+
+    let alice_box = SalsaBox::new(&PublicKey::from(public), &SecretKey::from(ALICE_SECRET_KEY));
+    let bob_box = SalsaBox::new(&PublicKey::from(ALICE_PUBLIC_KEY), &secret_key);
+    let nonce = GenericArray::from_slice(NONCE);
+    let ciphertext = alice_box.encrypt(nonce, PLAINTEXT).unwrap();
+    assert_eq!(bob_box.decrypt(nonce, &ciphertext[..]).unwrap(), PLAINTEXT);
+}
+
+#[test]
+fn decrypt_slice_nonce() {
+    let bob_box = bob_box();
+
+    assert_eq!(
+        bob_box
+            .decrypt_slice_nonce(NONCE, xsalsa20poly1305::CIPHERTEXT)
+            .unwrap(),
+        PLAINTEXT
+    );
+
+    let mut long_nonce = NONCE.to_vec();
+    long_nonce.push(0);
+    for nonce in &[&NONCE[..23], &long_nonce[..]] {
+        assert_eq!(
+            bob_box.decrypt_slice_nonce(nonce, xsalsa20poly1305::CIPHERTEXT),
+            Err(crypto_box::Error::InvalidNonceLength {
+                expected: 24,
+                got: nonce.len()
+            })
+        );
+    }
+}
+
+#[test]
+fn rewrap() {
+    let old_box = bob_box();
+    let new_box = SalsaBox::from_key(GenericArray::from([7; 32]));
+    let old_nonce = GenericArray::from_slice(NONCE);
+    let new_nonce = GenericArray::from_slice(&[1; 24]);
+
+    let rewrapped = crypto_box::rewrap(
+        &old_box,
+        old_nonce,
+        xsalsa20poly1305::CIPHERTEXT,
+        &new_box,
+        new_nonce,
+    )
+    .unwrap();
+    assert_eq!(
+        new_box.decrypt(new_nonce, &rewrapped[..]).unwrap(),
+        PLAINTEXT
+    );
+    assert!(old_box.decrypt(new_nonce, &rewrapped[..]).is_err());
+    assert!(old_box.decrypt(old_nonce, &rewrapped[..]).is_err());
+
+    assert_eq!(
+        crypto_box::rewrap(
+            &new_box,
+            old_nonce,
+            xsalsa20poly1305::CIPHERTEXT,
+            &old_box,
+            new_nonce
+        ),
+        Err(crypto_box::Error::Aead)
+    );
+}
+
+#[test]
+fn encrypt_large() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+
+    // Multiple chunks, with and without a partial final chunk, and a single
+    // chunk
+    for &chunk_size in &[16, 131, 1000] {
+        for &len in &[0, 32, 100, PLAINTEXT.len()] {
+            let plaintext = &PLAINTEXT[..len];
+            let blob = alice_box
+                .encrypt_large(&mut rand_core::OsRng, plaintext, chunk_size)
+                .unwrap();
+            assert_eq!(blob.len(), 19 + len + (len / chunk_size + 1) * 16);
+            assert_eq!(bob_box.decrypt_large(&blob, chunk_size).unwrap(), plaintext);
+        }
+    }
+
+    let blob = alice_box
+        .encrypt_large(&mut rand_core::OsRng, PLAINTEXT, 32)
+        .unwrap();
+    assert!(bob_box.decrypt_large(&blob, 33).is_err());
+
+    // Truncated, including at a chunk boundary
+    for &len in &[blob.len() - 1, 19 + 2 * 48, 19 + 15, 19, 18, 0] {
+        assert!(bob_box.decrypt_large(&blob[..len], 32).is_err());
+    }
+
+    // Reordered chunks
+    let mut reordered = blob.clone();
+    reordered[19..19 + 48].copy_from_slice(&blob[19 + 48..19 + 96]);
+    reordered[19 + 48..19 + 96].copy_from_slice(&blob[19..19 + 48]);
+    assert!(bob_box.decrypt_large(&reordered, 32).is_err());
+}
+
+#[test]
+#[cfg(feature = "hd")]
+fn derive_path() {
+    // SLIP-0010 test vector 1 for curve25519
+    let seed = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ];
+
+    let master = SecretKey::derive_path(&seed, "m").unwrap();
+    assert_eq!(
+        master.as_bytes(),
+        &[
+            0xd7, 0x0a, 0x59, 0xc2, 0xe6, 0x8b, 0x83, 0x6c, 0xc4, 0xbb, 0xe8, 0xbc, 0xae, 0x42,
+            0x51, 0x69, 0xb9, 0xe2, 0x38, 0x4f, 0x39, 0x05, 0x09, 0x1e, 0x3d, 0x60, 0xb8, 0x90,
+            0xe9, 0x0c, 0xd9, 0x2c
+        ]
+    );
+    assert_eq!(
+        master.public_key().as_bytes(),
+        &[
+            0x5c, 0x72, 0x89, 0xdc, 0x9f, 0x7f, 0x3e, 0xa1, 0xc8, 0xc2, 0xde, 0x73, 0x23, 0xb9,
+            0xfb, 0x07, 0x81, 0xf6, 0x9c, 0x9e, 0xcd, 0x6d, 0xe4, 0xf0, 0x95, 0xac, 0x89, 0xa0,
+            0x2d, 0xc8, 0x05, 0x77
+        ]
+    );
+
+    let child = SecretKey::derive_path(&seed, "m/0'").unwrap();
+    assert_eq!(
+        child.as_bytes(),
+        &[
+            0xcd, 0x76, 0x30, 0xd7, 0x51, 0x3c, 0xbe, 0x80, 0x51, 0x5f, 0x73, 0x17, 0xcd, 0xb9,
+            0xa4, 0x7a, 0xd4, 0xa5, 0x6b, 0x63, 0xc3, 0xf1, 0xdc, 0x29, 0x58, 0x3a, 0xb8, 0xd4,
+            0xcc, 0x25, 0xa9, 0xb2
+        ]
+    );
+    assert_eq!(
+        SecretKey::derive_path(&seed, "m/0H").unwrap().as_bytes(),
+        child.as_bytes()
+    );
+
+    for path in &[
+        "",
+        "0'",
+        "m/0",
+        "m/0'/",
+        "m/-1'",
+        "m/01'",
+        "m/2147483648'",
+        "m/x'",
+    ] {
+        assert_eq!(
+            SecretKey::derive_path(&seed, path).unwrap_err(),
+            crypto_box::Error::InvalidDerivationPath
+        );
+    }
+}
+
+#[test]
+fn box_ext() {
+    use crypto_box::{aead::AeadCore, BoxExt, IetfChaChaBox};
+
+    fn sizes<B: AeadCore + BoxExt>() -> (usize, usize) {
+        (B::nonce_size(), B::tag_size())
+    }
+
+    assert_eq!(sizes::<SalsaBox>(), (24, 16));
+    assert_eq!(sizes::<ChaChaBox>(), (24, 16));
+    assert_eq!(sizes::<IetfChaChaBox>(), (12, 16));
+}
+
+#[test]
+#[cfg(feature = "blake2")]
+fn key_id() {
+    let alice_box = alice_box();
+    let bob_box = bob_box();
+    assert_eq!(alice_box.key_id(), bob_box.key_id());
+
+    let key_id = alice_box.key_id();
+    let precomputed_key = crypto_box::PrecomputedKey::new(
+        &PublicKey::from(BOB_PUBLIC_KEY),
+        &SecretKey::from(ALICE_SECRET_KEY),
+    );
+    assert_ne!(&key_id[..], &precomputed_key.as_bytes()[..8]);
+    for _ in 0..16 {
+        let other_sk = SecretKey::generate(&mut rand_core::OsRng);
+        let other_box = SalsaBox::new(&PublicKey::from(BOB_PUBLIC_KEY), &other_sk);
+        assert_ne!(other_box.key_id(), key_id);
+    }
+}
+
+#[test]
+#[cfg(feature = "blake2")]
+fn encrypt_siv() {
+    let chacha_box = ChaChaBox::from_key(GenericArray::from([1; 32]));
+
+    let ciphertext = chacha_box.encrypt_siv(b"header", PLAINTEXT).unwrap();
+    assert_eq!(ciphertext.len(), PLAINTEXT.len() + 40);
+
+    // Known answer, pinning the derivation of the synthetic nonce PRF's key
+    assert_eq!(
+        ciphertext[..24],
+        [
+            0xa9, 0x23, 0x08, 0x63, 0x06, 0xff, 0x8b, 0x2e, 0xa2, 0x9a, 0x23, 0xbd, 0x6f, 0x15,
+            0x4e, 0x8c, 0x84, 0x8c, 0x3a, 0x38, 0x2f, 0x64, 0x8e, 0x55,
+        ]
+    );
+    assert_eq!(
+        chacha_box.decrypt_siv(b"header", &ciphertext).unwrap(),
+        PLAINTEXT
+    );
+
+    // Deterministic, but bound to the associated data and plaintext
+    assert_eq!(
+        chacha_box.encrypt_siv(b"header", PLAINTEXT).unwrap(),
+        ciphertext
+    );
+    assert_ne!(
+        chacha_box.encrypt_siv(b"other", PLAINTEXT).unwrap()[..24],
+        ciphertext[..24]
+    );
+    assert_ne!(
+        chacha_box.encrypt_siv(b"header", &PLAINTEXT[1..]).unwrap()[..24],
+        ciphertext[..24]
+    );
+
+    // Tampering with the nonce, ciphertext or associated data fails
+    for i in &[0, 24, ciphertext.len() - 1] {
+        let mut tampered = ciphertext.clone();
+        tampered[*i] ^= 1;
+        assert!(chacha_box.decrypt_siv(b"header", &tampered).is_err());
+    }
+    assert!(chacha_box.decrypt_siv(b"other", &ciphertext).is_err());
+    assert!(chacha_box
+        .decrypt_siv(b"header", &ciphertext[..39])
+        .is_err());
+    assert!(ChaChaBox::from_key(GenericArray::from([2; 32]))
+        .decrypt_siv(b"header", &ciphertext)
+        .is_err());
+
+    // A valid ciphertext under a nonce other than the synthetic one fails
+    let nonce = GenericArray::from_slice(NONCE);
+    let mut forged = NONCE.to_vec();
+    forged.extend_from_slice(
+        &chacha_box
+            .encrypt(
+                nonce,
+                crypto_box::aead::Payload {
+                    msg: PLAINTEXT,
+                    aad: b"header",
+                },
+            )
+            .unwrap(),
+    );
+    assert!(chacha_box.decrypt_siv(b"header", &forged).is_err());
+}
+
+#[test]
+fn from_key_wrapper() {
+    use crypto_box::PrecomputedKey;
+
+    /// Key held in (notionally) protected memory.
+    struct ProtectedKey(Box<[u8; 32]>);
+
+    impl AsRef<[u8; 32]> for ProtectedKey {
+        fn as_ref(&self) -> &[u8; 32] {
+            &self.0
+        }
+    }
+
+    let key = [7; 32];
+    let nonce = GenericArray::from_slice(NONCE);
+    let ciphertext = SalsaBox::from_key(ProtectedKey(Box::new(key)))
+        .encrypt(nonce, PLAINTEXT)
+        .unwrap();
+
+    // The key is copied out of borrowed guards, which keep ownership of it
+    let precomputed_key = PrecomputedKey::from(key);
+    assert_eq!(
+        SalsaBox::from_key(&precomputed_key)
+            .encrypt(nonce, PLAINTEXT)
+            .unwrap(),
+        ciphertext
+    );
+    assert_eq!(precomputed_key.as_bytes(), &key);
+    assert_eq!(
+        SalsaBox::from_key(GenericArray::from(key))
+            .encrypt(nonce, PLAINTEXT)
+            .unwrap(),
+        ciphertext
+    );
+
+    // Both boxes accept the same key types
+    let chacha_box = ChaChaBox::from_key(ProtectedKey(Box::new(key)));
+    let ciphertext = chacha_box.encrypt(nonce, PLAINTEXT).unwrap();
+    assert_eq!(
+        ChaChaBox::from_key(GenericArray::from(key))
+            .decrypt(nonce, &ciphertext[..])
+            .unwrap(),
+        PLAINTEXT
+    );
+}
+
+#[test]
+fn backend_info() {
+    let info = crypto_box::backend_info();
+
+    if cfg!(feature = "u64_backend") {
+        assert_eq!(info.name, "u64");
+        assert_eq!(info.constant_time, cfg!(target_pointer_width = "64"));
+    } else {
+        assert_eq!(info.name, "u32");
+        assert!(info.constant_time);
+    }
+}
+
+#[test]
+fn nonce_with_fixed_context() {
+    use crypto_box::nonce_with_fixed_context;
+
+    let context = [0x42; 16];
+    let first = nonce_with_fixed_context(&context, &[1; 8]);
+    let second = nonce_with_fixed_context(&context, &[2; 8]);
+
+    assert_ne!(first, second);
+    for nonce in &[first, second] {
+        assert_eq!(&nonce[..16], &context);
+    }
+    assert_eq!(&first[16..], &[1; 8]);
+    assert_eq!(&second[16..], &[2; 8]);
+}
+
+#[test]
+fn decrypt_and_wipe() {
+    let bob_box = bob_box();
+    let nonce = GenericArray::from_slice(NONCE);
+
+    // Left intact on failure
+    let mut tampered = xsalsa20poly1305::CIPHERTEXT.to_vec();
+    tampered[20] ^= 1;
+    let mut ciphertext = tampered.clone();
+    assert_eq!(
+        bob_box.decrypt_and_wipe(nonce, &mut ciphertext),
+        Err(crypto_box::Error::Aead)
+    );
+    assert_eq!(ciphertext, tampered);
+
+    // Zeroed on success
+    let mut ciphertext = xsalsa20poly1305::CIPHERTEXT.to_vec();
+    assert_eq!(
+        bob_box.decrypt_and_wipe(nonce, &mut ciphertext),
+        Ok(PLAINTEXT.to_vec())
+    );
+    assert_eq!(ciphertext, vec![0; xsalsa20poly1305::CIPHERTEXT.len()]);
+}
+
+#[test]
+fn secret_key_try_generate() {
+    use core::num::NonZeroU32;
+    use rand_core::{CryptoRng, RngCore};
+
+    /// RNG whose entropy source always fails.
+    struct FailingRng;
+
+    impl RngCore for FailingRng {
+        fn next_u32(&mut self) -> u32 {
+            rand_core::impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core::impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.try_fill_bytes(dest).unwrap()
+        }
+
+        fn try_fill_bytes(&mut self, _dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            let code = NonZeroU32::new(rand_core::Error::CUSTOM_START + 1).unwrap();
+            Err(rand_core::Error::from(code))
+        }
+    }
+
+    impl CryptoRng for FailingRng {}
+
+    let err = SecretKey::try_generate(&mut FailingRng).unwrap_err();
+    assert_eq!(
+        err.code(),
+        NonZeroU32::new(rand_core::Error::CUSTOM_START + 1)
+    );
+
+    let secret_key = SecretKey::try_generate(&mut rand_core::OsRng).unwrap();
+    assert_ne!(secret_key.as_bytes(), &[0; 32]);
+}
+
+#[test]
+fn secret_key_matches_public() {
+    let secret_key = SecretKey::from(ALICE_SECRET_KEY);
+    assert!(secret_key.matches_public(&PublicKey::from(ALICE_PUBLIC_KEY)));
+    assert!(!secret_key.matches_public(&PublicKey::from(BOB_PUBLIC_KEY)));
+}
+
+#[test]
+fn nonce_counter_endian() {
+    use crypto_box::{Endian, Nonce, NonceCounter};
+
+    let mut initial = Nonce::default();
+    initial[0] = 0xff;
+    initial[23] = 0xff;
+
+    // 0xff carries into the second byte
+    let mut little = NonceCounter::new(initial);
+    assert_eq!(little, NonceCounter::with_endian(initial, Endian::Little));
+    assert_eq!(little.next_nonce().unwrap(), initial);
+    let mut expected = Nonce::default();
+    expected[1] = 0x01;
+    expected[23] = 0xff;
+    assert_eq!(little.next_nonce().unwrap(), expected);
+
+    // 0xff carries into the second to last byte
+    let mut big = NonceCounter::with_endian(initial, Endian::Big);
+    assert_eq!(big.next_nonce().unwrap(), initial);
+    let mut expected = Nonce::default();
+    expected[0] = 0xff;
+    expected[22] = 0x01;
+    assert_eq!(big.next_nonce().unwrap(), expected);
+
+    // Counter value 0x0102 in each byte order
+    let mut big = NonceCounter::with_endian(Nonce::default(), Endian::Big);
+    let mut little = NonceCounter::with_endian(Nonce::default(), Endian::Little);
+    for _ in 0..0x0102 {
+        big.next_nonce().unwrap();
+        little.next_nonce().unwrap();
+    }
+    let big = big.next_nonce().unwrap();
+    let little = little.next_nonce().unwrap();
+    assert_eq!(big[22..], [0x01, 0x02]);
+    assert!(big[..22].iter().all(|&b| b == 0));
+    assert_eq!(little[..2], [0x02, 0x01]);
+    assert!(little[2..].iter().all(|&b| b == 0));
+
+    // Exhaustion
+    let mut big = NonceCounter::with_endian(*GenericArray::from_slice(&[0xff; 24]), Endian::Big);
+    assert!(big.next_nonce().is_ok());
+    assert!(big.next_nonce().is_err());
+}
+
+#[test]
+fn public_key_set() {
+    use crypto_box::PublicKeySet;
+
+    let pinned: PublicKeySet = vec![
+        PublicKey::from(ALICE_PUBLIC_KEY),
+        PublicKey::from(BOB_PUBLIC_KEY),
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(pinned.len(), 2);
+    assert!(bool::from(
+        pinned.contains_ct(&PublicKey::from(BOB_PUBLIC_KEY))
+    ));
+    assert!(!bool::from(pinned.contains_ct(&PublicKey::from([1; 32]))));
+    assert!(!bool::from(
+        PublicKeySet::new().contains_ct(&PublicKey::from(ALICE_PUBLIC_KEY))
+    ));
+}
+
+mod replay_window {
+    use super::*;
+    use crypto_box::ReplayWindow;
+
+    #[test]
+    fn in_window() {
+        let mut window = ReplayWindow::new(128);
+        assert!(window.check_and_update(10));
+        assert!(window.check_and_update(12));
+        assert!(window.check_and_update(11));
+        assert!(window.check_and_update(200));
+        assert!(window.check_and_update(73));
+        assert!(window.check_and_update(201));
+    }
+
+    #[test]
+    fn duplicate() {
+        let mut window = ReplayWindow::new(128);
+        assert!(window.check_and_update(5));
+        assert!(!window.check_and_update(5));
+        assert!(window.check_and_update(100));
+        assert!(!window.check_and_update(5));
+        assert!(!window.check_and_update(100));
+        assert!(window.check_and_update(4));
+        assert!(!window.check_and_update(4));
+    }
+
+    #[test]
+    fn too_old() {
+        let mut window = ReplayWindow::new(64);
+        assert!(window.check_and_update(1000));
+        assert!(window.check_and_update(937));
+        assert!(!window.check_and_update(936));
+        assert!(!window.check_and_update(0));
+    }
+
+    #[test]
+    fn authenticated_message_numbers() {
+        let alice_box = ChaChaBox::new(
+            &PublicKey::from(BOB_PUBLIC_KEY),
+            &SecretKey::from(ALICE_SECRET_KEY),
+        );
+        let bob_box = ChaChaBox::new(
+            &PublicKey::from(ALICE_PUBLIC_KEY),
+            &SecretKey::from(BOB_SECRET_KEY),
+        );
+        let mut window = ReplayWindow::new(64);
+
+        let mut receive = |seq: u64, nonce: &crypto_box::Nonce, ciphertext: &[u8]| {
+            let aad = seq.to_le_bytes();
+            bob_box
+                .decrypt(
+                    nonce,
+                    Payload {
+                        msg: ciphertext,
+                        aad: &aad,
+                    },
+                )
+                .is_ok()
+                && window.check_and_update(seq)
+        };
+
+        let nonce = alice_box.generate_nonce(&mut rand_core::OsRng);
+        let aad = 7u64.to_le_bytes();
+        let ciphertext = alice_box
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: PLAINTEXT,
+                    aad: &aad,
+                },
+            )
+            .unwrap();
+
+        assert!(receive(7, &nonce, &ciphertext));
+        assert!(!receive(7, &nonce, &ciphertext));
+        assert!(!receive(8, &nonce, &ciphertext));
+    }
+}
+
+#[cfg(feature = "testing")]
+mod testing {
+    use super::*;
+    use crypto_box::testing::encrypt_deterministic;
+
+    #[test]
+    fn encrypt_deterministic_matches_libsodium() {
+        let mut nonce = [0u8; 24];
+        nonce.copy_from_slice(NONCE);
+
+        assert_eq!(
+            encrypt_deterministic(&ALICE_SECRET_KEY, &BOB_PUBLIC_KEY, &nonce, PLAINTEXT),
+            xsalsa20poly1305::CIPHERTEXT
+        );
+    }
+}
+
+#[cfg(feature = "compat")]
+mod compat {
+    use super::*;
+    use crypto_box::compat;
+
+    #[test]
+    fn seal_open() {
+        let nonce = GenericArray::from_slice(NONCE);
+        let ciphertext = compat::seal(
+            PLAINTEXT,
+            nonce,
+            &PublicKey::from(BOB_PUBLIC_KEY),
+            &SecretKey::from(ALICE_SECRET_KEY),
+        )
+        .unwrap();
+        assert_eq!(xsalsa20poly1305::CIPHERTEXT, &ciphertext[..]);
+
+        let plaintext = compat::open(
+            &ciphertext,
+            nonce,
+            &PublicKey::from(ALICE_PUBLIC_KEY),
+            &SecretKey::from(BOB_SECRET_KEY),
+        )
+        .unwrap();
+        assert_eq!(PLAINTEXT, &plaintext[..]);
+    }
+
+    #[test]
+    fn precomputed() {
+        let nonce = GenericArray::from_slice(NONCE);
+        let alice_key = compat::precompute(
+            &PublicKey::from(BOB_PUBLIC_KEY),
+            &SecretKey::from(ALICE_SECRET_KEY),
+        );
+        let bob_key = compat::precompute(
+            &PublicKey::from(ALICE_PUBLIC_KEY),
+            &SecretKey::from(BOB_SECRET_KEY),
+        );
+        assert_eq!(alice_key.as_bytes(), bob_key.as_bytes());
+
+        let ciphertext = compat::seal_precomputed(PLAINTEXT, nonce, &alice_key).unwrap();
+        assert_eq!(xsalsa20poly1305::CIPHERTEXT, &ciphertext[..]);
+        let plaintext = compat::open_precomputed(&ciphertext, nonce, &bob_key).unwrap();
+        assert_eq!(PLAINTEXT, &plaintext[..]);
+    }
+
+    #[test]
+    fn gen_keypair_and_nonce() {
+        let (alice_pk, alice_sk) = compat::gen_keypair();
+        let (bob_pk, bob_sk) = compat::gen_keypair();
+        assert_eq!(alice_sk.public_key(), alice_pk);
+        assert_ne!(alice_pk, bob_pk);
+
+        let nonce = compat::gen_nonce();
+        assert_ne!(nonce, compat::gen_nonce());
+
+        let ciphertext = compat::seal(PLAINTEXT, &nonce, &bob_pk, &alice_sk).unwrap();
+        let plaintext = compat::open(&ciphertext, &nonce, &alice_pk, &bob_sk).unwrap();
+        assert_eq!(PLAINTEXT, &plaintext[..]);
+    }
+}
+
+#[cfg(feature = "seal")]
+mod seal {
+    use super::*;
+    use crypto_box::SealedBox;
+
+    #[test]
+    fn round_trip() {
+        let recipient_sk = SecretKey::from(BOB_SECRET_KEY);
+        let ciphertext =
+            SealedBox::seal(&mut rand_core::OsRng, &recipient_sk.public_key(), PLAINTEXT).unwrap();
+
+        assert_eq!(
+            SealedBox::open(&recipient_sk, &ciphertext).unwrap(),
+            PLAINTEXT
+        );
+        assert!(SealedBox::open(&SecretKey::from(ALICE_SECRET_KEY), &ciphertext).is_err());
+        assert!(SealedBox::open(&recipient_sk, &ciphertext[..31]).is_err());
+    }
+
+    #[test]
+    fn open_with_sender() {
+        let recipient_sk = SecretKey::from(BOB_SECRET_KEY);
+        let ciphertext =
+            SealedBox::seal(&mut rand_core::OsRng, &recipient_sk.public_key(), PLAINTEXT).unwrap();
+
+        let (ephemeral_pk, plaintext) =
+            SealedBox::open_with_sender(&recipient_sk, &ciphertext).unwrap();
+        assert_eq!(ephemeral_pk.as_bytes(), &ciphertext[..32]);
+        assert_eq!(plaintext, PLAINTEXT);
+    }
+
+    #[test]
+    fn detached() {
+        let recipient_sk = SecretKey::from(BOB_SECRET_KEY);
+        let recipient_pk = recipient_sk.public_key();
+
+        // Detached to combined
+        let (ephemeral_pk, ciphertext) =
+            SealedBox::seal_detached(&mut rand_core::OsRng, &recipient_pk, PLAINTEXT).unwrap();
+        let mut combined = ephemeral_pk.as_bytes().to_vec();
+        combined.extend_from_slice(&ciphertext);
+        assert_eq!(
+            SealedBox::open(&recipient_sk, &combined).unwrap(),
+            PLAINTEXT
+        );
+
+        // Combined to detached
+        let combined = SealedBox::seal(&mut rand_core::OsRng, &recipient_pk, PLAINTEXT).unwrap();
+        let (ephemeral_pk, ciphertext) = combined.split_at(32);
+        let mut ephemeral_pk_bytes = [0u8; 32];
+        ephemeral_pk_bytes.copy_from_slice(ephemeral_pk);
+        let ephemeral_pk = PublicKey::from(ephemeral_pk_bytes);
+        assert_eq!(
+            SealedBox::open_detached(&recipient_sk, &ephemeral_pk, ciphertext).unwrap(),
+            PLAINTEXT
+        );
+
+        // The ephemeral key is bound into the nonce
+        let other_pk = SecretKey::from(ALICE_SECRET_KEY).public_key();
+        assert!(SealedBox::open_detached(&recipient_sk, &other_pk, ciphertext).is_err());
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn seal_to_ed25519() {
+        // RFC 8032 test 1
+        let ed25519_seed = [
+            0x9d, 0x61, 0xb1, 0x9d, 0xef, 0xfd, 0x5a, 0x60, 0xba, 0x84, 0x4a, 0xf4, 0x92, 0xec,
+            0x2c, 0xc4, 0x44, 0x49, 0xc5, 0x69, 0x7b, 0x32, 0x69, 0x19, 0x70, 0x3b, 0xac, 0x03,
+            0x1c, 0xae, 0x7f, 0x60,
+        ];
+        let ed25519_pk = [
+            0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64,
+            0x07, 0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68,
+            0xf7, 0x07, 0x51, 0x1a,
+        ];
+
+        let recipient_sk = SecretKey::from_ed25519_seed(&ed25519_seed);
+        assert_eq!(
+            PublicKey::from_ed25519(&ed25519_pk).unwrap(),
+            recipient_sk.public_key()
+        );
+
+        let ciphertext =
+            SealedBox::seal_to_ed25519(&mut rand_core::OsRng, &ed25519_pk, PLAINTEXT).unwrap();
+        assert_eq!(
+            SealedBox::open(&recipient_sk, &ciphertext).unwrap(),
+            PLAINTEXT
+        );
+
+        // Not a point on the curve
+        let mut invalid_pk = [0; 32];
+        invalid_pk[0] = 2;
+        // Identity point, which has small order
+        let mut identity_pk = [0; 32];
+        identity_pk[0] = 1;
+        // Basepoint plus a point of order 8, outside the prime-order subgroup
+        let mixed_order_pk = [
+            0x98, 0x51, 0x9e, 0xad, 0xf3, 0x5b, 0x99, 0x52, 0x33, 0xb5, 0x1b, 0x5c, 0xd2, 0x3e,
+            0x9c, 0xc5, 0xa2, 0x8b, 0x63, 0x9b, 0x5a, 0x4a, 0xf0, 0xec, 0x90, 0x3c, 0xb9, 0x60,
+            0xd8, 0x1b, 0x78, 0x19,
+        ];
+        for pk in &[invalid_pk, identity_pk, mixed_order_pk] {
+            assert_eq!(
+                SealedBox::seal_to_ed25519(&mut rand_core::OsRng, pk, PLAINTEXT),
+                Err(crypto_box::Error::InvalidEd25519Key)
+            );
+        }
+    }
+
+    #[test]
+    fn sealed_len() {
+        let recipient_pk = PublicKey::from(BOB_PUBLIC_KEY);
+        for plaintext in &[&b""[..], b"x", PLAINTEXT] {
+            let ciphertext =
+                SealedBox::seal(&mut rand_core::OsRng, &recipient_pk, plaintext).unwrap();
+            assert_eq!(ciphertext.len(), SealedBox::sealed_len(plaintext.len()));
+        }
+        assert_eq!(SealedBox::sealed_len(0), 48);
+    }
+
+    #[test]
+    fn looks_like_sealed() {
+        let recipient_pk = PublicKey::from(BOB_PUBLIC_KEY);
+        let ciphertext = SealedBox::seal(&mut rand_core::OsRng, &recipient_pk, b"").unwrap();
+        assert_eq!(ciphertext.len(), 48);
+        assert!(SealedBox::looks_like_sealed(&ciphertext));
+
+        assert!(!SealedBox::looks_like_sealed(&ciphertext[..47]));
+        assert!(!SealedBox::looks_like_sealed(b"too short"));
+
+        // Ephemeral public key of small order
+        assert!(!SealedBox::looks_like_sealed(&[0; 48]));
+    }
+
+    #[test]
+    fn seal_const() {
+        let recipient_sk = SecretKey::from(BOB_SECRET_KEY);
+        let recipient_pk = recipient_sk.public_key();
+
+        // Exact fit
+        let (sealed, len) =
+            SealedBox::seal_const::<_, 179>(&mut rand_core::OsRng, &recipient_pk, PLAINTEXT)
+                .unwrap();
+        assert_eq!(len, 179);
+        assert_eq!(SealedBox::open(&recipient_sk, &sealed).unwrap(), PLAINTEXT);
+
+        // Spare capacity
+        let (sealed, len) =
+            SealedBox::seal_const::<_, 256>(&mut rand_core::OsRng, &recipient_pk, PLAINTEXT)
+                .unwrap();
+        assert_eq!(len, 179);
+        assert_eq!(
+            SealedBox::open(&recipient_sk, &sealed[..len]).unwrap(),
+            PLAINTEXT
+        );
+
+        assert_eq!(
+            SealedBox::seal_const::<_, 178>(&mut rand_core::OsRng, &recipient_pk, PLAINTEXT),
+            Err(crypto_box::Error::BufferTooSmall {
+                required: 179,
+                capacity: 178
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "heapless")]
+    fn in_place_heapless() {
+        use crypto_box::aead::heapless::Vec;
+
+        let recipient_sk = SecretKey::from(BOB_SECRET_KEY);
+        let recipient_pk = recipient_sk.public_key();
+
+        let mut buffer: Vec<u8, 179> = Vec::from_slice(PLAINTEXT).unwrap();
+        SealedBox::seal_in_place(&mut rand_core::OsRng, &recipient_pk, &mut buffer).unwrap();
+        assert_eq!(buffer.len(), PLAINTEXT.len() + 48);
+
+        // Interoperable with the allocating functions
+        assert_eq!(SealedBox::open(&recipient_sk, &buffer).unwrap(), PLAINTEXT);
+        SealedBox::open_in_place(&recipient_sk, &mut buffer).unwrap();
+        assert_eq!(&buffer[..], PLAINTEXT);
+
+        let ciphertext = SealedBox::seal(&mut rand_core::OsRng, &recipient_pk, PLAINTEXT).unwrap();
+        let mut buffer: Vec<u8, 179> = Vec::from_slice(&ciphertext).unwrap();
+        SealedBox::open_in_place(&recipient_sk, &mut buffer).unwrap();
+        assert_eq!(&buffer[..], PLAINTEXT);
+
+        // Not enough capacity for the ephemeral public key and tag
+        let mut buffer: Vec<u8, 178> = Vec::from_slice(PLAINTEXT).unwrap();
+        assert!(
+            SealedBox::seal_in_place(&mut rand_core::OsRng, &recipient_pk, &mut buffer).is_err()
+        );
+        assert_eq!(&buffer[..], PLAINTEXT);
+
+        // Failure leaves the buffer untouched
+        let mut buffer: Vec<u8, 179> = Vec::from_slice(&ciphertext).unwrap();
+        let alice_sk = SecretKey::from(ALICE_SECRET_KEY);
+        assert!(SealedBox::open_in_place(&alice_sk, &mut buffer).is_err());
+        assert_eq!(&buffer[..], &ciphertext[..]);
+        let mut buffer: Vec<u8, 179> = Vec::from_slice(&ciphertext[..47]).unwrap();
+        assert!(SealedBox::open_in_place(&recipient_sk, &mut buffer).is_err());
+    }
+}
+
+#[cfg(feature = "stream")]
+mod stream {
+    use super::*;
+    use crypto_box::stream::{DecryptReader, EncryptWriter, HEADER_SIZE};
+    use std::io::{Read, Write};
+
+    fn chacha_boxes() -> (ChaChaBox, ChaChaBox) {
+        let alice_sk = SecretKey::from(ALICE_SECRET_KEY);
+        let bob_sk = SecretKey::from(BOB_SECRET_KEY);
+        (
+            ChaChaBox::new(&bob_sk.public_key(), &alice_sk),
+            ChaChaBox::new(&alice_sk.public_key(), &bob_sk),
+        )
+    }
+
+    fn encrypt(chunk_size: usize, plaintext: &[u8]) -> Vec<u8> {
+        encrypt_with_aad(chunk_size, b"", plaintext)
+    }
+
+    fn encrypt_with_aad(chunk_size: usize, aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let mut writer = EncryptWriter::with_chunk_size(
+            chacha_boxes().0,
+            &mut rand_core::OsRng,
+            aad,
+            Vec::new(),
+            chunk_size,
+        )
+        .unwrap();
+        writer.write_all(plaintext).unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn decrypt(chunk_size: usize, ciphertext: &[u8]) -> std::io::Result<Vec<u8>> {
+        decrypt_with_aad(chunk_size, b"", ciphertext)
+    }
+
+    fn decrypt_with_aad(
+        chunk_size: usize,
+        aad: &[u8],
+        ciphertext: &[u8],
+    ) -> std::io::Result<Vec<u8>> {
+        let mut reader =
+            DecryptReader::with_chunk_size(chacha_boxes().1, aad, ciphertext, chunk_size)?;
+        let mut plaintext = Vec::new();
+        reader.read_to_end(&mut plaintext)?;
+        Ok(plaintext)
+    }
+
+    /// Plaintext byte at the given stream position.
+    fn pattern(position: u64) -> u8 {
+        (position % 251) as u8
+    }
+
+    /// Sink which checks the plaintext written to it against [`pattern`],
+    /// without storing it.
+    #[derive(Default)]
+    struct PatternSink {
+        position: u64,
+        max_write: usize,
+    }
+
+    impl Write for PatternSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            for byte in buf {
+                assert_eq!(*byte, pattern(self.position));
+                self.position += 1;
+            }
+            self.max_write = self.max_write.max(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Shared queue of ciphertext bytes produced by an [`EncryptWriter`].
+    #[derive(Clone, Default)]
+    struct Queue(std::rc::Rc<std::cell::RefCell<std::collections::VecDeque<u8>>>);
+
+    impl Write for Queue {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Reader which lazily encrypts `len` bytes of [`pattern`] plaintext, so
+    /// that large streams never have to be held in memory.
+    struct EncryptingReader {
+        writer: Option<EncryptWriter<ChaChaBox, Queue>>,
+        queue: Queue,
+        position: u64,
+        len: u64,
+        max_queued: usize,
+    }
+
+    impl EncryptingReader {
+        fn new(len: u64) -> Self {
+            let queue = Queue::default();
+            let writer =
+                EncryptWriter::new(chacha_boxes().0, &mut rand_core::OsRng, b"", queue.clone())
+                    .unwrap();
+            EncryptingReader {
+                writer: Some(writer),
+                queue,
+                position: 0,
+                len,
+                max_queued: 0,
+            }
+        }
+    }
+
+    impl Read for EncryptingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            while self.queue.0.borrow().is_empty() {
+                let writer = match self.writer.as_mut() {
+                    Some(writer) => writer,
+                    None => return Ok(0),
+                };
+
+                if self.position == self.len {
+                    self.writer.take().unwrap().finish()?;
+                    continue;
+                }
+
+                let mut plaintext = [0u8; 4096];
+                let len = (self.len - self.position).min(plaintext.len() as u64) as usize;
+                for byte in plaintext[..len].iter_mut() {
+                    *byte = pattern(self.position);
+                    self.position += 1;
+                }
+                writer.write_all(&plaintext[..len])?;
+            }
+
+            let mut queue = self.queue.0.borrow_mut();
+            self.max_queued = self.max_queued.max(queue.len());
+            let len = buf.len().min(queue.len());
+            for (out, byte) in buf.iter_mut().zip(queue.drain(..len)) {
+                *out = byte;
+            }
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for len in &[0, 1, 15, 16, 17, 48, PLAINTEXT.len()] {
+            let plaintext = &PLAINTEXT[..*len];
+            let ciphertext = encrypt(16, plaintext);
+            assert_eq!(ciphertext.len(), HEADER_SIZE + len + (len / 16 + 1) * 16);
+            assert_eq!(decrypt(16, &ciphertext).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn salsa_round_trip() {
+        let alice_sk = SecretKey::from(ALICE_SECRET_KEY);
+        let bob_sk = SecretKey::from(BOB_SECRET_KEY);
+
+        let mut writer = EncryptWriter::new(
+            SalsaBox::new(&bob_sk.public_key(), &alice_sk),
+            &mut rand_core::OsRng,
+            b"",
+            Vec::new(),
+        )
+        .unwrap();
+        writer.write_all(PLAINTEXT).unwrap();
+        let ciphertext = writer.finish().unwrap();
+
+        let mut reader = DecryptReader::new(
+            SalsaBox::new(&alice_sk.public_key(), &bob_sk),
+            b"",
+            ciphertext.as_slice(),
+        )
+        .unwrap();
+        let mut plaintext = Vec::new();
+        reader.read_to_end(&mut plaintext).unwrap();
+        assert_eq!(plaintext, PLAINTEXT);
+    }
+
+    #[test]
+    fn truncation_and_tampering() {
+        let ciphertext = encrypt(16, &PLAINTEXT[..48]);
+
+        // Dropping the final (empty) chunk
+        assert!(decrypt(16, &ciphertext[..ciphertext.len() - 16]).is_err());
+
+        // Dropping a full chunk
+        let mut truncated = ciphertext[..HEADER_SIZE].to_vec();
+        truncated.extend_from_slice(&ciphertext[HEADER_SIZE + 32..]);
+        assert!(decrypt(16, &truncated).is_err());
+
+        let mut tampered = ciphertext.clone();
+        tampered[HEADER_SIZE] ^= 1;
+        assert!(decrypt(16, &tampered).is_err());
+
+        assert!(decrypt(32, &ciphertext).is_err());
+    }
+
+    #[test]
+    fn errors_are_sticky() {
+        let ciphertext = encrypt(16, &PLAINTEXT[..48]);
+        let mut buf = [0u8; 64];
+
+        // Retrying after a failed chunk never reports the end of the stream
+        let mut tampered = ciphertext.clone();
+        tampered[HEADER_SIZE + 32] ^= 1;
+        let mut reader =
+            DecryptReader::with_chunk_size(chacha_boxes().1, b"", tampered.as_slice(), 16).unwrap();
+        assert_eq!(reader.read(&mut buf).unwrap(), 16);
+        for _ in 0..3 {
+            let err = reader.read(&mut buf).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        }
+        assert!(reader.copy_to(&mut Vec::new()).is_err());
+
+        // Likewise for truncated streams, whose final chunk is missing
+        let truncated = &ciphertext[..ciphertext.len() - 16];
+        let mut reader =
+            DecryptReader::with_chunk_size(chacha_boxes().1, b"", truncated, 16).unwrap();
+        let mut plaintext = Vec::new();
+        assert!(reader.read_to_end(&mut plaintext).is_err());
+        assert!(reader.read_to_end(&mut plaintext).is_err());
+        assert!(reader.copy_to(&mut plaintext).is_err());
+        assert_eq!(
+            reader.read(&mut buf).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+
+        // And for errors from the underlying reader, which mustn't expose the
+        // partially read chunk
+        struct FailingReader<'a>(&'a [u8]);
+
+        impl Read for FailingReader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.0.is_empty() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionReset,
+                        "reset",
+                    ));
+                }
+                self.0.read(buf)
+            }
+        }
+
+        let reader = FailingReader(&ciphertext[..HEADER_SIZE + 40]);
+        let mut reader = DecryptReader::with_chunk_size(chacha_boxes().1, b"", reader, 16).unwrap();
+        assert_eq!(reader.read(&mut buf).unwrap(), 16);
+        assert_eq!(
+            reader.read(&mut buf).unwrap_err().kind(),
+            std::io::ErrorKind::ConnectionReset
+        );
+        assert_eq!(
+            reader.read(&mut buf).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn write_errors_are_sticky() {
+        /// Writer which fails on its `fail_at`-th write (counting from 1).
+        #[derive(Debug)]
+        struct FailingWriter {
+            writes: usize,
+            fail_at: usize,
+        }
+
+        impl Write for FailingWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.writes += 1;
+                if self.writes == self.fail_at {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionReset,
+                        "reset",
+                    ));
+                }
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let new_writer = |fail_at| {
+            let inner = FailingWriter { writes: 0, fail_at };
+            EncryptWriter::with_chunk_size(chacha_boxes().0, &mut rand_core::OsRng, b"", inner, 16)
+                .unwrap()
+        };
+
+        // The header is the first write, so the first chunk fails to write
+        let mut writer = new_writer(2);
+        assert_eq!(
+            writer.write(&PLAINTEXT[..16]).unwrap_err().kind(),
+            std::io::ErrorKind::ConnectionReset
+        );
+        for _ in 0..3 {
+            assert_eq!(
+                writer.write(&PLAINTEXT[16..32]).unwrap_err().kind(),
+                std::io::ErrorKind::BrokenPipe
+            );
+        }
+        assert_eq!(
+            writer.flush().unwrap_err().kind(),
+            std::io::ErrorKind::BrokenPipe
+        );
+        assert_eq!(
+            writer.finish().unwrap_err().kind(),
+            std::io::ErrorKind::BrokenPipe
+        );
+
+        // Successful writes before the failing chunk are unaffected
+        let mut writer = new_writer(3);
+        writer.write_all(&PLAINTEXT[..24]).unwrap();
+        assert!(writer.write_all(&PLAINTEXT[24..]).is_err());
+        assert_eq!(
+            writer.finish().unwrap_err().kind(),
+            std::io::ErrorKind::BrokenPipe
+        );
+    }
+
+    #[test]
+    fn aad() {
+        let ciphertext = encrypt_with_aad(16, b"file header", &PLAINTEXT[..48]);
+        assert_eq!(
+            decrypt_with_aad(16, b"file header", &ciphertext).unwrap(),
+            &PLAINTEXT[..48]
+        );
+
+        // The mismatch is detected when decrypting the first chunk
+        let mut reader = DecryptReader::with_chunk_size(
+            chacha_boxes().1,
+            b"other header",
+            ciphertext.as_slice(),
+            16,
+        )
+        .unwrap();
+        let mut buf = [0u8; 1];
+        assert!(reader.read(&mut buf).is_err());
+
+        assert!(decrypt_with_aad(16, b"", &ciphertext).is_err());
+    }
+
+    #[cfg(feature = "blake2")]
+    #[test]
+    fn encrypt_hashed() {
+        use blake2::{Blake2b512, Digest};
+
+        let (ciphertext, hash) = crypto_box::stream::encrypt_hashed(
+            chacha_boxes().0,
+            &mut rand_core::OsRng,
+            PLAINTEXT,
+            Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(hash[..], Blake2b512::digest(PLAINTEXT)[..]);
+
+        let mut reader = DecryptReader::new(chacha_boxes().1, b"", ciphertext.as_slice()).unwrap();
+        let mut plaintext = Vec::new();
+        reader.read_to_end(&mut plaintext).unwrap();
+        assert_eq!(plaintext, PLAINTEXT);
+    }
+
+    #[test]
+    fn copy_to_bounded_memory() {
+        use crypto_box::stream::DEFAULT_CHUNK_SIZE;
+
+        // Simulates a large stream which is never held in memory as a whole
+        let len = 256 * DEFAULT_CHUNK_SIZE as u64 + 1234;
+        let mut reader =
+            DecryptReader::new(chacha_boxes().1, b"", EncryptingReader::new(len)).unwrap();
+        let mut sink = PatternSink::default();
+
+        assert_eq!(reader.copy_to(&mut sink).unwrap(), len);
+        assert_eq!(sink.position, len);
+        assert!(sink.max_write <= DEFAULT_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn copy_to_emits_only_verified_chunks() {
+        let plaintext = &PLAINTEXT[..64];
+        let mut ciphertext = encrypt(16, plaintext);
+
+        // Corrupt the third chunk: the first two are written, but nothing
+        // from the corrupted chunk is
+        ciphertext[HEADER_SIZE + 2 * 32] ^= 1;
+        let mut reader =
+            DecryptReader::with_chunk_size(chacha_boxes().1, b"", &ciphertext[..], 16).unwrap();
+        let mut written = Vec::new();
+        assert!(reader.copy_to(&mut written).is_err());
+        assert_eq!(written, &plaintext[..32]);
+
+        // Truncated stream
+        let ciphertext = encrypt(16, plaintext);
+        let mut reader = DecryptReader::with_chunk_size(
+            chacha_boxes().1,
+            b"",
+            &ciphertext[..ciphertext.len() - 32],
+            16,
+        )
+        .unwrap();
+        let mut written = Vec::new();
+        assert!(reader.copy_to(&mut written).is_err());
+        assert_eq!(written, &plaintext[..48]);
+    }
+}
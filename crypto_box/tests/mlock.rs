@@ -0,0 +1,45 @@
+//! Tests for secret keys locked into RAM with the `mlock` feature.
+
+#![cfg(all(feature = "std", feature = "mlock"))]
+
+use crypto_box::{
+    aead::{generic_array::GenericArray, Aead},
+    DiffieHellman, LockedSecretKey, SalsaBox, SecretKey,
+};
+
+const ALICE_SECRET_KEY: [u8; 32] = [
+    0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2, 0x66, 0x45,
+    0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5, 0x1d, 0xb9, 0x2c, 0x2a,
+];
+
+#[test]
+fn generate_and_drop() {
+    let locked_key = LockedSecretKey::generate(&mut rand_core::OsRng);
+    assert_ne!(locked_key.secret_key().as_bytes(), &[0; 32]);
+
+    // Locking may legitimately fail, e.g. under a low `RLIMIT_MEMLOCK`, but
+    // the key must stay usable either way
+    let _ = locked_key.is_locked();
+    assert_eq!(
+        locked_key.public_key(),
+        locked_key.secret_key().public_key()
+    );
+    drop(locked_key);
+}
+
+#[test]
+fn from_secret_key() {
+    let locked_key = LockedSecretKey::from(SecretKey::from(ALICE_SECRET_KEY));
+    assert_eq!(locked_key.secret_key().as_bytes(), &ALICE_SECRET_KEY);
+
+    let peer_pk = SecretKey::generate(&mut rand_core::OsRng).public_key();
+    let nonce = GenericArray::from_slice(&[0; 24]);
+    assert_eq!(
+        SalsaBox::new(&peer_pk, &locked_key)
+            .encrypt(nonce, &b"message"[..])
+            .unwrap(),
+        SalsaBox::new(&peer_pk, &SecretKey::from(ALICE_SECRET_KEY))
+            .encrypt(nonce, &b"message"[..])
+            .unwrap()
+    );
+}
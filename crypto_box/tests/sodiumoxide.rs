@@ -0,0 +1,93 @@
+//! Interoperability tests against libsodium (via `sodiumoxide`).
+
+#![cfg(all(feature = "std", feature = "seal"))]
+
+use crypto_box::{PublicKey, SealedBox, SecretKey};
+use rand_core::OsRng;
+use sodiumoxide::crypto::{box_ as reference_box, sealedbox as reference};
+
+const PLAINTEXT: &[u8] = b"sealed box interoperability test message";
+
+#[test]
+fn reference_opens_sealed_box() {
+    let (reference_pk, reference_sk) = reference_box::gen_keypair();
+    let recipient_pk = PublicKey::from(reference_pk.0);
+
+    let ciphertext = SealedBox::seal(&mut OsRng, &recipient_pk, PLAINTEXT).unwrap();
+    let plaintext = reference::open(&ciphertext, &reference_pk, &reference_sk).unwrap();
+
+    assert_eq!(PLAINTEXT, &plaintext[..]);
+}
+
+#[test]
+fn opens_reference_sealed_box() {
+    let recipient_sk = SecretKey::generate(&mut OsRng);
+    let recipient_pk = reference_box::PublicKey(*recipient_sk.public_key().as_bytes());
+
+    let ciphertext = reference::seal(PLAINTEXT, &recipient_pk);
+    let plaintext = SealedBox::open(&recipient_sk, &ciphertext).unwrap();
+
+    assert_eq!(PLAINTEXT, &plaintext[..]);
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn seal_to_reference_ed25519_key() {
+    use sodiumoxide::crypto::sign::ed25519;
+
+    let seed = [0x42; 32];
+    let (ed25519_pk, ed25519_sk) = ed25519::keypair_from_seed(&ed25519::Seed(seed));
+    let recipient_sk = SecretKey::from_ed25519_seed(&seed);
+
+    let ciphertext = SealedBox::seal_to_ed25519(&mut OsRng, &ed25519_pk.0, PLAINTEXT).unwrap();
+    let plaintext = SealedBox::open(&recipient_sk, &ciphertext).unwrap();
+    assert_eq!(PLAINTEXT, &plaintext[..]);
+
+    // The converted keys match libsodium's
+    let reference_pk = ed25519::to_curve25519_pk(&ed25519_pk).unwrap();
+    let reference_sk = ed25519::to_curve25519_sk(&ed25519_sk).unwrap();
+    assert_eq!(recipient_sk.as_bytes(), &reference_sk.0);
+    let plaintext = reference::open(&ciphertext, &reference_pk, &reference_sk).unwrap();
+    assert_eq!(PLAINTEXT, &plaintext[..]);
+
+    // Points outside the prime-order subgroup are rejected by both: this is
+    // the basepoint plus a point of order 8
+    let mixed_order_pk = [
+        0x98, 0x51, 0x9e, 0xad, 0xf3, 0x5b, 0x99, 0x52, 0x33, 0xb5, 0x1b, 0x5c, 0xd2, 0x3e, 0x9c,
+        0xc5, 0xa2, 0x8b, 0x63, 0x9b, 0x5a, 0x4a, 0xf0, 0xec, 0x90, 0x3c, 0xb9, 0x60, 0xd8, 0x1b,
+        0x78, 0x19,
+    ];
+    assert!(ed25519::to_curve25519_pk(&ed25519::PublicKey(mixed_order_pk)).is_err());
+    assert_eq!(
+        PublicKey::from_ed25519(&mixed_order_pk),
+        Err(crypto_box::Error::InvalidEd25519Key)
+    );
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn encrypt_deterministic_matches_reference() {
+    use crypto_box::testing::encrypt_deterministic;
+    use rand_core::RngCore;
+
+    for len in 0..64 {
+        let mut sk = [0u8; 32];
+        let mut pk = [0u8; 32];
+        let mut nonce = [0u8; 24];
+        OsRng.fill_bytes(&mut sk);
+        OsRng.fill_bytes(&mut pk);
+        OsRng.fill_bytes(&mut nonce);
+        let plaintext = &PLAINTEXT[..len.min(PLAINTEXT.len())];
+
+        let reference_ciphertext = reference_box::seal(
+            plaintext,
+            &reference_box::Nonce(nonce),
+            &reference_box::PublicKey(pk),
+            &reference_box::SecretKey(sk),
+        );
+        assert_eq!(
+            encrypt_deterministic(&sk, &pk, &nonce, plaintext),
+            reference_ciphertext
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Tests for the telemetry events emitted with the `tracing` feature.
+
+#![cfg(all(feature = "std", feature = "tracing"))]
+
+use crypto_box::{
+    aead::{generic_array::GenericArray, Aead},
+    SalsaBox, SecretKey,
+};
+use std::sync::{Arc, Mutex};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// Fields of an event, as `(name, value)` strings.
+type Fields = Vec<(String, String)>;
+
+/// Subscriber which captures the fields of every event.
+#[derive(Clone, Default)]
+struct CapturingSubscriber {
+    events: Arc<Mutex<Vec<Fields>>>,
+}
+
+struct FieldVisitor(Fields);
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .push((field.name().to_string(), format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name().to_string(), value.to_string()));
+    }
+}
+
+impl Subscriber for CapturingSubscriber {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = FieldVisitor(Vec::new());
+        event.record(&mut visitor);
+        self.events.lock().unwrap().push(visitor.0);
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+impl CapturingSubscriber {
+    fn has_event(&self, expected: &[(&str, &str)]) -> bool {
+        self.events.lock().unwrap().iter().any(|fields| {
+            expected
+                .iter()
+                .all(|(name, value)| fields.iter().any(|(n, v)| n == name && v == value))
+        })
+    }
+}
+
+#[test]
+fn authentication_failure_event() {
+    let secret_key = SecretKey::generate(&mut rand_core::OsRng);
+    let salsa_box = SalsaBox::new(&secret_key.public_key(), &secret_key);
+    let nonce = GenericArray::from_slice(&[0x42; 24]);
+
+    let subscriber = CapturingSubscriber::default();
+    tracing::subscriber::with_default(subscriber.clone(), || {
+        let mut ciphertext = salsa_box.encrypt(nonce, &b"telemetry"[..]).unwrap();
+        ciphertext[0] ^= 1;
+        assert!(salsa_box.decrypt(nonce, &ciphertext[..]).is_err());
+    });
+
+    assert!(subscriber.has_event(&[
+        ("cipher", "SalsaBox"),
+        ("operation", "encrypt"),
+        ("len", "9"),
+    ]));
+    assert!(subscriber.has_event(&[
+        ("cipher", "SalsaBox"),
+        ("operation", "decrypt"),
+        ("message", "authentication failed"),
+    ]));
+
+    // No event includes any message bytes
+    assert!(!subscriber
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .flatten()
+        .any(|(_, value)| value.contains("telemetry")));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn decrypt_ct_event() {
+    let secret_key = SecretKey::generate(&mut rand_core::OsRng);
+    let salsa_box = SalsaBox::new(&secret_key.public_key(), &secret_key);
+    let nonce = GenericArray::from_slice(&[0x42; 24]);
+    let mut ciphertext = salsa_box.encrypt(nonce, &b"telemetry"[..]).unwrap();
+    ciphertext[0] ^= 1;
+
+    let subscriber = CapturingSubscriber::default();
+    tracing::subscriber::with_default(subscriber.clone(), || {
+        let (choice, _) = salsa_box.decrypt_ct(nonce, &ciphertext);
+        assert!(!bool::from(choice));
+    });
+
+    assert!(subscriber.has_event(&[
+        ("cipher", "SalsaBox"),
+        ("operation", "decrypt"),
+        ("message", "authentication failed"),
+    ]));
+}
+
+#[test]
+fn encrypt_bounded_skips_cipher() {
+    let secret_key = SecretKey::generate(&mut rand_core::OsRng);
+    let salsa_box = SalsaBox::new(&secret_key.public_key(), &secret_key);
+    let nonce = GenericArray::from_slice(&[0x42; 24]);
+
+    let subscriber = CapturingSubscriber::default();
+    tracing::subscriber::with_default(subscriber.clone(), || {
+        assert!(salsa_box.encrypt_bounded(nonce, &[0; 1024], 1023).is_err());
+    });
+
+    // The cipher emits an event for every operation it performs
+    assert!(subscriber.events.lock().unwrap().is_empty());
+}
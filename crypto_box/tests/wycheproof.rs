@@ -0,0 +1,141 @@
+//! Tests against the [Project Wycheproof] test vectors.
+//!
+//! The vectors in `tests/data/wycheproof` are copied verbatim from the
+//! `testvectors_v1` directory of <https://github.com/C2SP/wycheproof>. To
+//! update them, replace the files with their latest upstream versions.
+//!
+//! Wycheproof has no XSalsa20Poly1305 vectors: [`SalsaBox`] is covered by
+//! the X25519 vectors here and by the libsodium interoperability tests.
+//!
+//! [Project Wycheproof]: https://github.com/C2SP/wycheproof
+//! [`SalsaBox`]: crypto_box::SalsaBox
+
+#![cfg(feature = "std")]
+
+use crypto_box::aead::{generic_array::GenericArray, Aead, Payload};
+use crypto_box::{ChaChaBox, DiffieHellman, PublicKey, SecretKey};
+use serde_json::Value;
+
+/// Load a Wycheproof test vector file, returning its tests along with the
+/// group each belongs to.
+fn load(name: &str) -> Vec<(Value, Value)> {
+    let path = format!(
+        "{}/tests/data/wycheproof/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    let file = std::fs::read_to_string(path).unwrap();
+    let vectors: Value = serde_json::from_str(&file).unwrap();
+
+    let mut tests = Vec::new();
+    for group in vectors["testGroups"].as_array().unwrap() {
+        for test in group["tests"].as_array().unwrap() {
+            tests.push((group.clone(), test.clone()));
+        }
+    }
+    assert_eq!(
+        tests.len() as u64,
+        vectors["numberOfTests"].as_u64().unwrap()
+    );
+    tests
+}
+
+fn hex(test: &Value, field: &str) -> Vec<u8> {
+    let hex = test[field].as_str().unwrap();
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn key(test: &Value, field: &str) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&hex(test, field));
+    key
+}
+
+fn has_flag(test: &Value, flag: &str) -> bool {
+    test["flags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|f| f.as_str() == Some(flag))
+}
+
+#[test]
+fn x25519() {
+    for (_, test) in load("x25519_test.json") {
+        let tc_id = &test["tcId"];
+        let public_key = PublicKey::from(key(&test, "public"));
+        let shared = SecretKey::from(key(&test, "private")).diffie_hellman(&public_key);
+
+        // All X25519 vectors are either valid or acceptable: the shared
+        // secret is always computed as specified
+        assert_ne!(test["result"], "invalid", "test {}", tc_id);
+        assert_eq!(&shared[..], &hex(&test, "shared")[..], "test {}", tc_id);
+
+        // Low order public keys, which yield an all-zero shared secret, are
+        // rejected by the validity check
+        let zero_shared = has_flag(&test, "ZeroSharedSecret");
+        assert!(
+            !zero_shared || has_flag(&test, "LowOrderPublic"),
+            "test {}",
+            tc_id
+        );
+        assert_eq!(shared == [0; 32], zero_shared, "test {}", tc_id);
+        assert_eq!(
+            bool::from(public_key.is_valid_ct()),
+            !has_flag(&test, "LowOrderPublic"),
+            "test {}",
+            tc_id
+        );
+    }
+}
+
+#[test]
+fn xchacha20poly1305() {
+    for (group, test) in load("xchacha20_poly1305_test.json") {
+        let tc_id = &test["tcId"];
+
+        // Nonces of other sizes are unrepresentable with `ChaChaBox`
+        if group["ivSize"] != 192 {
+            assert_eq!(test["result"], "invalid", "test {}", tc_id);
+            assert!(has_flag(&test, "InvalidNonceSize"), "test {}", tc_id);
+            continue;
+        }
+
+        let chacha_box = ChaChaBox::from_key(GenericArray::from(key(&test, "key")));
+        let nonce = hex(&test, "iv");
+        let nonce = GenericArray::from_slice(&nonce);
+        let aad = hex(&test, "aad");
+        let msg = hex(&test, "msg");
+        let mut ciphertext = hex(&test, "ct");
+        ciphertext.extend_from_slice(&hex(&test, "tag"));
+
+        let decrypted = chacha_box.decrypt(
+            nonce,
+            Payload {
+                msg: &ciphertext,
+                aad: &aad,
+            },
+        );
+
+        match test["result"].as_str().unwrap() {
+            "valid" => {
+                let encrypted = chacha_box
+                    .encrypt(
+                        nonce,
+                        Payload {
+                            msg: &msg,
+                            aad: &aad,
+                        },
+                    )
+                    .unwrap();
+                assert_eq!(encrypted, ciphertext, "test {}", tc_id);
+                assert_eq!(decrypted.unwrap(), msg, "test {}", tc_id);
+            }
+            "invalid" => assert!(decrypted.is_err(), "test {}", tc_id),
+            result => panic!("test {}: unexpected result {}", tc_id, result),
+        }
+    }
+}
@@ -98,7 +98,7 @@ mod tests {
 
     #[test]
     fn from_secretkey_yield_same() {
-        let keypair = KeyPair::generate(&mut OsRng);
+        let keypair = KeyPair::generate(OsRng);
 
         let reconstructed_keypair =
             KeyPair::from(SecretKey::from(keypair.secret().as_dalek().to_bytes()));
@@ -68,9 +68,9 @@ mod tests {
 
     #[test]
     fn can_be_constructed_by_serialized() {
-        let header = Header::generate(&mut OsRng);
+        let header = Header::generate(OsRng);
 
-        let reconstructed_header = Header::from(header);
+        let reconstructed_header = Header::from(*header.as_ref());
 
         assert_eq!(header.as_ref(), reconstructed_header.as_ref());
     }
@@ -59,7 +59,7 @@ mod tests {
 
     #[test]
     fn can_be_constructed_by_serialized() {
-        let key = Key::generate(&mut OsRng);
+        let key = Key::generate(OsRng);
 
         let reconstructed_key = Key::from(*key.as_ref());
 
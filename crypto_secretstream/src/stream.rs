@@ -278,9 +278,7 @@ impl AeadInPlace for Stream {
         cipher
             .try_apply_keystream(&mut tag_block)
             .map_err(|_| aead::Error)?;
-        let tag = tag_block[0];
-        tag_block[0] = buffer[0];
-        buffer[0] = tag;
+        core::mem::swap(&mut tag_block[0], &mut buffer[0]);
 
         // compute mac and reject if not matching
         let mac_output =
@@ -16,9 +16,9 @@ const MESSAGES: [(&[u8], Tag, &[u8]); 5] = [
 fn pushed_can_be_pulled() {
     use rand_core::OsRng;
 
-    let key = Key::generate(&mut OsRng);
+    let key = Key::generate(OsRng);
 
-    let (header, mut push_stream) = PushStream::init(&mut OsRng, &key);
+    let (header, mut push_stream) = PushStream::init(OsRng, &key);
 
     let header = reference::Header::from_slice(header.as_ref()).expect("create Header");
     let key = reference::Key::from_slice(key.as_ref()).expect("create Key");
@@ -31,7 +31,7 @@ fn pushed_can_be_pulled() {
             .expect("push in stream");
 
         let (cleartext, pulled_tag) = pull_stream
-            .pull(&mut ciphertext, Some(additional_data))
+            .pull(&ciphertext, Some(additional_data))
             .expect("to pull from Stream");
 
         assert_eq!(*tag as u8, pulled_tag as u8);